@@ -0,0 +1,47 @@
+#![feature(test, try_from)]
+
+// This is a little weird, see
+// https://github.com/rust-lang/rust/pull/54116#issuecomment-422294107
+// for details
+extern crate test;
+
+mod batch {
+    use crate::test::Bencher;
+    use ffsvm::{DenseProblem, DenseSVM, ModelFile, Predict, Problem};
+    use std::convert::TryFrom;
+
+    /// Produces a model and a batch of problems for benchmarking
+    #[allow(dead_code)]
+    fn produce_batch(total_sv: u32, num_attributes: u32, num_problems: usize) -> (DenseSVM, Vec<DenseProblem>) {
+        let raw_model = ModelFile::random_dense("c_svc", "rbf", total_sv, num_attributes);
+        let svm = DenseSVM::try_from(&raw_model).unwrap();
+
+        let problems = (0 .. num_problems)
+            .map(|p| {
+                let mut problem = Problem::from(&svm);
+                let problem_mut = problem.features().as_slice_mut();
+
+                for i in 0 .. num_attributes {
+                    problem_mut[i as usize] = (i as usize + p) as f32;
+                }
+
+                problem
+            })
+            .collect();
+
+        (svm, problems)
+    }
+
+    #[bench]
+    fn predict_batch_rbf_sv1024_attr16_problems10000(b: &mut Bencher) {
+        let (svm, mut problems) = produce_batch(1024, 16, 10_000);
+        b.iter(|| svm.predict_batch(&mut problems).expect("This should work"));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[bench]
+    fn predict_batch_parallel_rbf_sv1024_attr16_problems10000(b: &mut Bencher) {
+        let (svm, mut problems) = produce_batch(1024, 16, 10_000);
+        b.iter(|| svm.predict_batch_parallel(&mut problems).expect("This should work"));
+    }
+}
@@ -0,0 +1,48 @@
+#![feature(test, try_from)]
+
+// This is a little weird, see
+// https://github.com/rust-lang/rust/pull/54116#issuecomment-422294107
+// for details
+extern crate test;
+
+mod precision {
+    use crate::test::Bencher;
+    use ffsvm::{DenseSVM, ModelFile, Predict, PrecisionMode, Problem};
+    use std::convert::TryFrom;
+
+    /// Produces a test case run for benchmarking
+    #[allow(dead_code)]
+    fn produce_testcase(precision: PrecisionMode, nr_class: u32, sv_per_class: u32, num_attributes: u32) -> impl FnMut() {
+        let raw_model = ModelFile::random_dense_multiclass("c_svc", "rbf", nr_class, sv_per_class, num_attributes);
+        let mut svm = DenseSVM::try_from(&raw_model).unwrap();
+        let mut problem = Problem::from(&svm);
+        let problem_mut = problem.features().as_slice_mut();
+
+        svm.set_precision_mode(precision);
+
+        for i in 0 .. num_attributes {
+            problem_mut[i as usize] = i as f32;
+        }
+
+        move || {
+            (&svm)
+                .predict_value(&mut problem)
+                .expect("This should work")
+        }
+    }
+
+    #[bench]
+    fn predict_fast_class10_sv128_attr16(b: &mut Bencher) {
+        b.iter(produce_testcase(PrecisionMode::Fast, 10, 128, 16));
+    }
+
+    #[bench]
+    fn predict_adaptive_class10_sv128_attr16(b: &mut Bencher) {
+        b.iter(produce_testcase(PrecisionMode::Adaptive { epsilon: 1e-3 }, 10, 128, 16));
+    }
+
+    #[bench]
+    fn predict_precise_class10_sv128_attr16(b: &mut Bencher) {
+        b.iter(produce_testcase(PrecisionMode::Precise, 10, 128, 16));
+    }
+}
@@ -0,0 +1,45 @@
+#![feature(test, try_from)]
+
+// This is a little weird, see
+// https://github.com/rust-lang/rust/pull/54116#issuecomment-422294107
+// for details
+extern crate test;
+
+mod probability {
+    use crate::test::Bencher;
+    use ffsvm::{DenseSVM, ModelFile, Predict, Problem};
+    use std::convert::TryFrom;
+
+    /// Produces a test case run for benchmarking
+    #[allow(dead_code)]
+    fn produce_testcase(kernel_type: &str, nr_class: u32, sv_per_class: u32, num_attributes: u32) -> impl FnMut() {
+        let raw_model = ModelFile::random_dense_multiclass("c_svc", kernel_type, nr_class, sv_per_class, num_attributes);
+        let svm = DenseSVM::try_from(&raw_model).unwrap();
+        let mut problem = Problem::from(&svm);
+        let problem_mut = problem.features().as_slice_mut();
+
+        for i in 0 .. num_attributes {
+            problem_mut[i as usize] = i as f32;
+        }
+
+        // Solver might not converge for random models, which is fine for benchmarking.
+        move || {
+            let _ = (&svm).predict_probability(&mut problem);
+        }
+    }
+
+    #[bench]
+    fn predict_probability_class10_sv8_attr16(b: &mut Bencher) {
+        b.iter(produce_testcase("linear", 10, 8, 16));
+    }
+
+    #[bench]
+    fn predict_probability_class50_sv8_attr16(b: &mut Bencher) {
+        b.iter(produce_testcase("linear", 50, 8, 16));
+    }
+
+    #[bench]
+    fn predict_probability_class100_sv8_attr16(b: &mut Bencher) {
+        b.iter(produce_testcase("linear", 100, 8, 16));
+    }
+}
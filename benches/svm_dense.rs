@@ -68,6 +68,22 @@ mod svm_dense {
         b.iter(produce_testcase("c_svc", "linear", 1024, 1024));
     }
 
+    #[bench]
+    fn predict_linear_fast_sv1024_attr16(b: &mut Bencher) {
+        let raw_model = ModelFile::random_dense("c_svc", "linear", 1024, 16);
+        let mut svm = DenseSVM::try_from(&raw_model).unwrap();
+        svm.enable_linear_fast().unwrap();
+
+        let mut problem = Problem::from(&svm);
+        let problem_mut = problem.features().as_slice_mut();
+
+        for i in 0..16 {
+            problem_mut[i as usize] = i as f32;
+        }
+
+        b.iter(|| svm.predict_value(&mut problem).expect("This should work"));
+    }
+
     // Poly
 
     #[bench]
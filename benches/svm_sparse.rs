@@ -34,6 +34,62 @@ mod svm_sparse {
         }
     }
 
+    /// Produces a model whose support vectors have wildly varying numbers of non-zero attributes
+    #[allow(dead_code)]
+    fn produce_variable_density_testcase(total_sv: u32, num_attributes: u32, grouped: bool) -> impl FnMut() {
+        let mut model = format!(
+            "svm_type c_svc\nkernel_type rbf\ngamma 0.5\nnr_class 2\ntotal_sv {}\nrho 0.1\nlabel 0 1\nnr_sv {} {}\nSV\n",
+            total_sv,
+            total_sv / 2,
+            total_sv - total_sv / 2
+        );
+
+        for i in 0..total_sv {
+            // Alternate between almost empty and almost full vectors.
+            let step = if i % 2 == 0 { num_attributes / 4 } else { 1 };
+            let coefficient = if i < total_sv / 2 { 1.0 } else { -1.0 };
+
+            model.push_str(&format!("{} ", coefficient));
+
+            for a in (i % step.max(1)..num_attributes).step_by(step.max(1) as usize) {
+                model.push_str(&format!("{}:{} ", a, (a as f32 * 0.01).sin()));
+            }
+
+            model.push('\n');
+        }
+
+        let mut svm = SparseSVM::try_from(model.as_str()).unwrap();
+
+        if grouped {
+            svm.group_support_vectors();
+        }
+
+        let mut problem = Problem::from(&svm);
+        let problem_mut = problem.features();
+
+        for i in 0..num_attributes {
+            problem_mut[i as usize] = i as f32;
+        }
+
+        move || {
+            (&svm)
+                .predict_value(&mut problem)
+                .expect("This should work")
+        }
+    }
+
+    // Variable density
+
+    #[bench]
+    fn predict_variable_density_sv1024_attr1024(b: &mut Bencher) {
+        b.iter(produce_variable_density_testcase(1024, 1024, false));
+    }
+
+    #[bench]
+    fn predict_variable_density_grouped_sv1024_attr1024(b: &mut Bencher) {
+        b.iter(produce_variable_density_testcase(1024, 1024, true));
+    }
+
     // RBF
 
     #[bench]
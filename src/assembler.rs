@@ -0,0 +1,161 @@
+use crate::errors::Error;
+
+/// A named range of features inside the full feature vector.
+#[derive(Clone, Debug)]
+struct Block {
+    name: String,
+    offset: usize,
+    len: usize,
+}
+
+/// Helps building a feature vector from multiple sources.
+///
+/// # Description
+///
+/// If features come from different subsystems (e.g., image and meta data) the model usually
+/// expects them concatenated at fixed offsets. The `FeatureAssembler` lets you register named
+/// blocks once, fill them independently, and then obtain the full feature vector.
+///
+/// ```
+/// use ffsvm::*;
+///
+/// fn main() -> Result<(), Error> {
+///     let mut assembler = FeatureAssembler::new(4);
+///
+///     assembler.add_block("image", 0, 3)?;
+///     assembler.add_block("meta", 3, 1)?;
+///
+///     assembler.set_block("image", &[0.1, 0.2, 0.3])?;
+///     assembler.set_block("meta", &[42.0])?;
+///
+///     assert_eq!(assembler.assemble()?, vec![0.1, 0.2, 0.3, 42.0]);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FeatureAssembler {
+    /// All registered blocks.
+    blocks: Vec<Block>,
+
+    /// The assembled features.
+    features: Vec<f32>,
+}
+
+impl FeatureAssembler {
+    /// Creates a new assembler for a model with the given number of attributes, see [SVMCore::attributes].
+    pub fn new(num_attributes: usize) -> FeatureAssembler {
+        FeatureAssembler {
+            blocks: Vec::new(),
+            features: vec![0.0; num_attributes],
+        }
+    }
+
+    /// Registers a block `name` covering `len` features starting at `offset`.
+    ///
+    /// Returns [Error::DimensionMismatch] if the block does not fit into the feature vector, and
+    /// [Error::InvalidBlock] if it overlaps another block or the name is already taken.
+    pub fn add_block(&mut self, name: &str, offset: usize, len: usize) -> Result<(), Error> {
+        if offset + len > self.features.len() {
+            return Err(Error::DimensionMismatch {
+                expected: self.features.len(),
+                actual: offset + len,
+            });
+        }
+
+        for block in &self.blocks {
+            let overlaps = offset < block.offset + block.len && block.offset < offset + len;
+
+            if overlaps || block.name == name {
+                return Err(Error::InvalidBlock(name.to_owned()));
+            }
+        }
+
+        self.blocks.push(Block {
+            name: name.to_owned(),
+            offset,
+            len,
+        });
+
+        Ok(())
+    }
+
+    /// Copies `values` into the block `name`.
+    ///
+    /// Returns [Error::InvalidBlock] for unknown names and [Error::DimensionMismatch] if `values`
+    /// does not have the block's length.
+    pub fn set_block(&mut self, name: &str, values: &[f32]) -> Result<(), Error> {
+        let block = self
+            .blocks
+            .iter()
+            .find(|b| b.name == name)
+            .ok_or_else(|| Error::InvalidBlock(name.to_owned()))?;
+
+        if values.len() != block.len {
+            return Err(Error::DimensionMismatch {
+                expected: block.len,
+                actual: values.len(),
+            });
+        }
+
+        self.features[block.offset .. block.offset + block.len].copy_from_slice(values);
+
+        Ok(())
+    }
+
+    /// Returns the full feature vector.
+    ///
+    /// Returns [Error::DimensionMismatch] unless the registered blocks cover all attributes.
+    pub fn assemble(&self) -> Result<Vec<f32>, Error> {
+        let covered = self.blocks.iter().map(|b| b.len).sum::<usize>();
+
+        if covered != self.features.len() {
+            return Err(Error::DimensionMismatch {
+                expected: self.features.len(),
+                actual: covered,
+            });
+        }
+
+        Ok(self.features.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeatureAssembler;
+    use crate::Error;
+
+    #[test]
+    fn assembles_blocks() -> Result<(), Error> {
+        let mut assembler = FeatureAssembler::new(5);
+
+        assembler.add_block("meta", 3, 2)?;
+        assembler.add_block("image", 0, 3)?;
+
+        assembler.set_block("image", &[1.0, 2.0, 3.0])?;
+        assembler.set_block("meta", &[4.0, 5.0])?;
+
+        assert_eq!(assembler.assemble()?, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn detects_mismatches() -> Result<(), Error> {
+        let mut assembler = FeatureAssembler::new(5);
+
+        assembler.add_block("image", 0, 3)?;
+
+        match assembler.assemble() {
+            Err(Error::DimensionMismatch { expected: 5, actual: 3 }) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        assert!(assembler.set_block("image", &[1.0, 2.0]).is_err());
+        assert!(assembler.set_block("meta", &[1.0, 2.0]).is_err());
+        assert!(assembler.add_block("meta", 2, 2).is_err());
+        assert!(assembler.add_block("meta", 4, 2).is_err());
+
+        Ok(())
+    }
+}
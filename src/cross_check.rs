@@ -0,0 +1,82 @@
+use crate::{
+    errors::Error,
+    parser::ModelFile,
+    svm::{
+        predict::Predict,
+        problem::{Problem, Solution},
+        DenseSVM, SparseSVM,
+    },
+};
+
+use std::convert::TryFrom;
+
+/// Predicts `inputs` with both the dense and the sparse representation of `model` and returns
+/// the indices of all inputs where both disagree.
+///
+/// # Description
+///
+/// [DenseSVM] and [SparseSVM] should produce numerically equivalent results for the same model,
+/// so any divergence points to a bug in one of them. Inputs disagree if their labels (or one-class
+/// results) differ, or their regression results or any pairwise decision value differ by more
+/// than `tol`. Zero features are left out of the sparse problems, like libSVM does.
+///
+/// Inputs are indexed by attribute index, i.e., feature `i` of an input is attribute `i` of the
+/// model in both representations. The dense representation is therefore loaded like
+/// [DenseSVM::try_from_indexed], and for 1-based models feature `0` of every input is unused.
+///
+/// Only available with the `testing` feature.
+///
+/// # Returns
+///
+/// The indices of all diverging inputs, any error loading `model` into either representation,
+/// or [Error::DimensionMismatch] if an input does not match the model.
+pub fn cross_check_representations(model: &ModelFile<'_>, inputs: &[Vec<f32>], tol: f64) -> Result<Vec<usize>, Error> {
+    let dense = DenseSVM::from_model_file(model, true)?;
+    let sparse = SparseSVM::try_from(model)?;
+
+    let mut problem_dense = Problem::from(&dense);
+    let mut problem_sparse = Problem::from(&sparse);
+    let mut diverging = Vec::new();
+
+    for (i, input) in inputs.iter().enumerate() {
+        if input.len() != dense.attributes() {
+            return Err(Error::DimensionMismatch {
+                expected: dense.attributes(),
+                actual: input.len(),
+            });
+        }
+
+        problem_dense.features().as_slice_mut()[.. input.len()].copy_from_slice(input);
+        problem_sparse.clear();
+
+        for (index, value) in input.iter().enumerate().filter(|(_, value)| **value != 0.0) {
+            problem_sparse.features()[index] = *value;
+        }
+
+        dense.predict_value(&mut problem_dense)?;
+        sparse.predict_value(&mut problem_sparse)?;
+
+        let solutions_match = match (problem_dense.solution(), problem_sparse.solution()) {
+            (Solution::Value(_), Solution::Value(_)) => {
+                let a = problem_dense.regression_value().unwrap_or(std::f64::NAN);
+                let b = problem_sparse.regression_value().unwrap_or(std::f64::NAN);
+
+                (a - b).abs() <= tol
+            }
+            (a, b) => a == b,
+        };
+
+        let decision_values_match = problem_dense
+            .decision_values
+            .data
+            .iter()
+            .zip(&problem_sparse.decision_values.data)
+            .all(|(a, b)| (a - b).abs() <= tol);
+
+        if !solutions_match || !decision_values_match {
+            diverging.push(i);
+        }
+    }
+
+    Ok(diverging)
+}
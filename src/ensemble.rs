@@ -0,0 +1,227 @@
+use crate::{
+    errors::Error,
+    svm::{
+        predict::Predict,
+        problem::{DenseProblem, Problem, Solution},
+        DenseSVM,
+    },
+    util::find_max_index,
+};
+
+/// How the members of a [SVMEnsemble] are combined.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Voting {
+    /// Every member predicts a label, the label predicted most often wins.
+    Hard,
+
+    /// Every member predicts probabilities, the label with the highest average probability wins.
+    /// Requires all members to be probability models.
+    Soft,
+}
+
+/// Combines the predictions of multiple [DenseSVM] sharing the same label set.
+///
+/// # Description
+///
+/// Each member may have been trained on a different feature subset, so every member gets its
+/// own [Problem], see [SVMEnsemble::problems]. Fill in the features of each problem and then
+/// call [SVMEnsemble::predict_value].
+///
+/// ```
+/// #![feature(try_from)]
+///
+/// use ffsvm::*;
+/// use std::convert::TryFrom;
+///
+/// fn main() -> Result<(), Error> {
+///     let members = vec![DenseSVM::try_from(SAMPLE_MODEL)?, DenseSVM::try_from(SAMPLE_MODEL)?];
+///     let ensemble = SVMEnsemble::new(members)?;
+///     let mut problems = ensemble.problems();
+///
+///     for problem in &mut problems {
+///         problem.features()[0] = 0.55838;
+///     }
+///
+///     assert_eq!(ensemble.predict_value(&mut problems, Voting::Hard)?, 42);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct SVMEnsemble {
+    /// All members.
+    members: Vec<DenseSVM>,
+
+    /// The shared labels, in the class order of the first member.
+    labels: Vec<i32>,
+}
+
+impl SVMEnsemble {
+    /// Creates a new ensemble from the given members.
+    ///
+    /// Returns [Error::EmptyModel] if `members` is empty and [Error::LabelSetMismatch] unless
+    /// all members have the same labels (in any order).
+    pub fn new(members: Vec<DenseSVM>) -> Result<SVMEnsemble, Error> {
+        let labels = members.first().ok_or(Error::EmptyModel)?.labels().collect::<Vec<_>>();
+
+        for (i, member) in members.iter().enumerate().skip(1) {
+            let same_labels = member.classes() == labels.len() && labels.iter().all(|l| member.class_index_for_label(*l).is_some());
+
+            if !same_labels {
+                return Err(Error::LabelSetMismatch { member: i });
+            }
+        }
+
+        Ok(SVMEnsemble { members, labels })
+    }
+
+    /// Returns all members of this ensemble.
+    pub fn members(&self) -> &[DenseSVM] { &self.members }
+
+    /// Returns the labels shared by all members.
+    pub fn labels(&self) -> &[i32] { &self.labels }
+
+    /// Creates one [Problem] per member, in member order. The problems can be reused for
+    /// subsequent predictions.
+    pub fn problems(&self) -> Vec<DenseProblem> { self.members.iter().map(Problem::from).collect() }
+
+    /// Predicts all problems with their respective member and combines the results.
+    ///
+    /// For [Voting::Hard], ties go to the label coming first in [SVMEnsemble::labels].
+    ///
+    /// # Returns
+    ///
+    /// The winning label, [Error::DimensionMismatch] if there is not exactly one problem per
+    /// member, [Error::NotClassifier] for regression or one-class members, [Error::NoProbabilities]
+    /// for [Voting::Soft] if a member has no probability model, [Error::UnknownLabel] if a
+    /// member predicted a label not in [SVMEnsemble::labels], or any error of the members' own
+    /// predictions.
+    pub fn predict_value(&self, problems: &mut [DenseProblem], voting: Voting) -> Result<i32, Error> {
+        if problems.len() != self.members.len() {
+            return Err(Error::DimensionMismatch {
+                expected: self.members.len(),
+                actual: problems.len(),
+            });
+        }
+
+        let mut scores = vec![0.0; self.labels.len()];
+
+        for (member, problem) in self.members.iter().zip(problems.iter_mut()) {
+            match voting {
+                Voting::Hard => {
+                    member.predict_value(problem)?;
+
+                    match problem.solution() {
+                        Solution::Label(label) => scores[self.label_index(label)?] += 1.0,
+                        _ => return Err(Error::NotClassifier),
+                    }
+                }
+                Voting::Soft => {
+                    member.predict_probability(problem)?;
+
+                    for (i, probability) in problem.probabilities().iter().enumerate().take(self.labels.len()) {
+                        let label = member.class_label_for_index(i)?;
+                        scores[self.label_index(label)?] += probability / self.members.len() as f64;
+                    }
+                }
+            }
+        }
+
+        Ok(self.labels[find_max_index(&scores)])
+    }
+
+    /// Returns the index of `label` in [SVMEnsemble::labels], or [Error::UnknownLabel] if a member
+    /// predicted a label we don't have. Member labels were checked against ours in `new`, so this
+    /// should not happen.
+    fn label_index(&self, label: i32) -> Result<usize, Error> { self.labels.iter().position(|l| *l == label).ok_or(Error::UnknownLabel { label }) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SVMEnsemble, Voting};
+    use crate::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn hard_voting() -> Result<(), Error> {
+        let mut members = vec![DenseSVM::try_from(SAMPLE_MODEL)?, DenseSVM::try_from(SAMPLE_MODEL)?, DenseSVM::try_from(SAMPLE_MODEL)?];
+
+        // Makes the last two members predict 21 for the features below.
+        members[1].set_rho(0, 1, -0.35)?;
+        members[2].set_rho(0, 1, -0.35)?;
+
+        let ensemble = SVMEnsemble::new(members)?;
+        let mut problems = ensemble.problems();
+
+        for problem in &mut problems {
+            problem.features()[0] = 0.23122;
+        }
+
+        assert_eq!(ensemble.predict_value(&mut problems, Voting::Hard)?, 21);
+        assert_eq!(problems[0].solution(), Solution::Label(42));
+
+        match ensemble.predict_value(&mut problems[.. 2], Voting::Hard) {
+            Err(Error::DimensionMismatch { expected: 3, actual: 2 }) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn soft_voting() -> Result<(), Error> {
+        let model = include_str!("../tests/data_dense/m_csvm_rbf_prob.libsvm");
+        let svm = DenseSVM::try_from(model)?;
+        let ensemble = SVMEnsemble::new(vec![DenseSVM::try_from(model)?, DenseSVM::try_from(model)?])?;
+
+        let mut problem = Problem::from(&svm);
+        let mut problems = ensemble.problems();
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3;
+            problems[0].features()[i] = 0.3;
+            problems[1].features()[i] = 0.3;
+        }
+
+        svm.predict_probability(&mut problem)?;
+
+        assert_eq!(Solution::Label(ensemble.predict_value(&mut problems, Voting::Soft)?), problem.solution());
+
+        let plain = SVMEnsemble::new(vec![DenseSVM::try_from(SAMPLE_MODEL)?])?;
+
+        match plain.predict_value(&mut plain.problems(), Voting::Soft) {
+            Err(Error::NoProbabilities) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn label_mismatch() -> Result<(), Error> {
+        let members = vec![
+            DenseSVM::try_from(SAMPLE_MODEL)?,
+            DenseSVM::try_from(include_str!("../tests/data_dense/m_csvm_rbf_prob.libsvm"))?,
+        ];
+
+        match SVMEnsemble::new(members) {
+            Err(Error::LabelSetMismatch { member: 1 }) => {}
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Expected Error::LabelSetMismatch"),
+        }
+
+        assert!(SVMEnsemble::new(Vec::new()).is_err());
+
+        // Bypasses the check in `new`, so the member predicts a label the ensemble doesn't know.
+        let inconsistent = SVMEnsemble {
+            members: vec![DenseSVM::try_from(SAMPLE_MODEL)?],
+            labels: vec![7, 8],
+        };
+
+        match inconsistent.predict_value(&mut inconsistent.problems(), Voting::Hard) {
+            Err(Error::UnknownLabel { label }) => assert!(label == 21 || label == 42),
+            other => panic!("Expected Error::UnknownLabel, got {:?}", other),
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    io,
     num::{ParseFloatError, ParseIntError},
     option::NoneError,
 };
@@ -8,15 +9,17 @@ use std::{
 pub enum Error {
     /// This can be emitted when creating a [SVM] from a [ModelFile]. For models generated by
     /// libSVM's `svm-train`, the most common reason this occurs is skipping attributes.
-    /// All attributes must be in sequential order 0, 1, 2, ..., n. If they are not, this
-    /// error will be emitted. For more details see the documentation provided in [ModelFile].
+    /// For a [DenseSVM] the attributes of each support vector must be in sequential order, e.g.,
+    /// 0, 1, 2, ..., n or 1, 2, 3, ..., n. If they are not, this error will be emitted; models skipping zero-valued attributes should be loaded
+    /// as [SparseSVM] instead, which only requires strictly increasing indices. For more details
+    /// see the documentation provided in [ModelFile].
     AttributesUnordered {
         /// The index process that was not a direct successor of the previous index. Can be used for
         /// easier debugging the model file.
         index: u32,
 
         /// The value of the given index. Can be used for debugging in conjunction with `index`.
-        value: f32,
+        value: f64,
 
         /// The last index processed. If everything were alright, then `index` should equal
         /// `last_index + 1`.
@@ -28,8 +31,144 @@ pub enum Error {
     NoProbabilities,
 
     /// Can be emitted by [Predict::predict_probability()] when predicting probabilities
-    /// and the internal iteration limit was exceeded.
-    IterationsExceeded,
+    /// and the internal iteration limit was exceeded. The fields describe how close the
+    /// solver got, so callers can decide whether the partial result in [Problem::probabilities]
+    /// is good enough.
+    IterationsExceeded {
+        /// Number of iterations performed.
+        iterations: usize,
+
+        /// The largest remaining error of the last iteration.
+        max_error: f64,
+
+        /// The error below which the solver would have considered itself converged.
+        eps: f64,
+    },
+
+    /// Emitted by classification-only methods such as [SVMCore::predict_votes] when they
+    /// are invoked on a regression (ε-SVR or ν-SVR) model.
+    NotClassifier,
+
+    /// Emitted by [SVMCore::enable_linear_fast] for models not using a linear kernel.
+    NotLinear,
+
+    /// Emitted by [SVMCore::decision_values_for_gammas], [SVMCore::set_rbf_gamma] and
+    /// [Problem::feature_contributions] for models not using an RBF kernel.
+    NotRbf,
+
+    /// Emitted when a slice or matrix passed in does not match the dimensions of the model.
+    DimensionMismatch {
+        /// The length the model expected.
+        expected: usize,
+
+        /// The length that was actually provided.
+        actual: usize,
+    },
+
+    /// Emitted by [SVMCore::validate_inputs] and [Problem::validate_finite] for features that are
+    /// `NaN` or infinite.
+    NonFiniteFeature {
+        /// The index of the offending feature.
+        index: usize,
+
+        /// The offending value.
+        value: f32,
+    },
+
+    /// Emitted by [Problem::set_feature] and [Problem::set_features_sparse] for feature indices
+    /// not below the number of attributes.
+    InvalidFeatureIndex {
+        /// The offending index.
+        index: usize,
+
+        /// The number of attributes of the model.
+        attributes: usize,
+    },
+
+    /// Emitted when a pair of class indices `(i, j)` does not denote a valid decision function,
+    /// which requires `i < j < classes`.
+    InvalidClassPair {
+        /// The first class index.
+        i: usize,
+
+        /// The second class index.
+        j: usize,
+    },
+
+    /// Emitted by [SVMCore::decision_without_sv] for support vector indices not below the number
+    /// of support vectors of the class.
+    InvalidSupportVector {
+        /// The class index.
+        class: usize,
+
+        /// The offending support vector index.
+        index: usize,
+    },
+
+    /// Emitted by [FeatureAssembler] for unknown, duplicate or overlapping blocks.
+    InvalidBlock(String),
+
+    /// Emitted when creating a [SVMCore] from a model without any support vectors, or a
+    /// [SVMEnsemble] without any members.
+    EmptyModel,
+
+    /// Emitted when creating a C-SVC or ν-SVC [SVMCore] from a model with fewer than two classes.
+    /// Such models can't separate anything and would predict the same label for every input.
+    InsufficientClasses {
+        /// The number of classes (`nr_class`) found in the model.
+        found: usize,
+    },
+
+    /// Emitted when creating a [SVMCore] from a model whose header is inconsistent, e.g., a `label`
+    /// list not having `nr_class` entries, `rho` not having one entry per class pair, or an
+    /// unknown `kernel_type`.
+    MalformedModel {
+        /// The name of the offending header field, as written in the model file.
+        field: &'static str,
+    },
+
+    /// Emitted by [Evaluation::new] and [DenseSVM::calibrate_threshold_for_fpr] for samples whose
+    /// true label is not one of the model's labels, and by [SVMEnsemble::predict_value] for
+    /// member predictions not among the ensemble's labels.
+    UnknownLabel {
+        /// The offending label.
+        label: i32,
+    },
+
+    /// Emitted when creating a [SVMEnsemble] from members that do not share the same label set.
+    LabelSetMismatch {
+        /// The index of the first member whose labels differ from the first member's.
+        member: usize,
+    },
+
+    /// Emitted when creating a [SVMCore] from a model whose support vectors don't have one
+    /// coefficient per other class (or exactly one for regression and one-class models).
+    CoefficientMatrixShape {
+        /// The 0-based index of the offending support vector.
+        vector_index: usize,
+
+        /// The number of coefficients expected.
+        expected: usize,
+
+        /// The number of coefficients found.
+        actual: usize,
+    },
+
+    /// Emitted by [SVMCore::verify_self_consistency] for models violating a structural invariant,
+    /// with a description of the violation.
+    InconsistentModel(String),
+
+    /// Emitted by [SparseSVM::to_dense] for models with too few stored attributes to be worth
+    /// converting, see [SparseSVM::MIN_DENSITY].
+    TooSparse {
+        /// The density of the model, see [SparseSVM::density].
+        density: f64,
+    },
+
+    /// Emitted by [SparseSVM::to_dense], [RandomSvmBuilder::build], [DenseSVM::to_model_string]
+    /// and when serializing a [DenseSVM] for models using a custom kernel, which can't be carried
+    /// over.
+    CustomKernel,
 
     /// If the model does not have a `gamma` set this error may be raised.
     NoGamma,
@@ -40,6 +179,48 @@ pub enum Error {
     /// If the model does not have a `degree` set this error may be raised.
     NoDegree,
 
+    /// Emitted when a line of the model header (everything up to and including the `SV` line)
+    /// could not be parsed.
+    HeaderParse {
+        /// The 1-based line number in the model file.
+        line: usize,
+
+        /// Description of what went wrong.
+        detail: String,
+    },
+
+    /// Emitted when a support vector line (everything past the `SV` line) could not be parsed.
+    SupportVectorParse {
+        /// The 1-based line number in the model file.
+        line: usize,
+
+        /// The 0-based index of the offending support vector.
+        vector_index: usize,
+
+        /// Description of what went wrong.
+        detail: String,
+    },
+
+    /// Emitted when a support vector line is empty, has no `index:value` pairs, or ends in a
+    /// dangling `index:` without a value, e.g., because the model file was cut off.
+    TruncatedSupportVector {
+        /// The 1-based line number in the model file.
+        line: usize,
+    },
+
+    /// Emitted by [DenseSVM::predict_csv] and [DenseSVM::predict_jsonl] for rows that could not be
+    /// parsed or don't have one column per attribute.
+    CsvRow {
+        /// The 1-based line number in the input.
+        line: usize,
+
+        /// Description of what went wrong.
+        detail: String,
+    },
+
+    /// Wrapper for I/O errors, e.g., when reading a model or writing predictions.
+    Io(String),
+
     /// Wrapper for internal parsing error when unifiying error handling.
     ParsingError(String),
 }
@@ -73,3 +254,7 @@ impl From<ParseIntError> for Error {
         Error::ParsingError("ParseIntError".to_owned())
     }
 }
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self { Error::Io(format!("{}", e)) }
+}
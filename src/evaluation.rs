@@ -0,0 +1,150 @@
+use crate::{
+    errors::Error,
+    svm::{problem::DenseProblem, DenseSVM},
+};
+
+/// Confusion matrix of a classifier on a labeled dataset.
+///
+/// # Description
+///
+/// Predicts every sample of a held-out set with a single, reused [Problem] and counts how often
+/// each true label was predicted as which label. Accuracy and per-class precision and recall are
+/// derived from these counts:
+///
+/// ```
+/// #![feature(try_from)]
+///
+/// use ffsvm::*;
+/// use std::convert::TryFrom;
+///
+/// fn main() -> Result<(), Error> {
+///     let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+///     let samples = vec![(vec![0.0001, 0.0001, 0.0001, 0.0001], 21), (vec![1.2974607, 1.0227317, 1.2545854, 0.0], 42)];
+///
+///     let evaluation = Evaluation::new(&svm, samples)?;
+///
+///     assert_eq!(evaluation.accuracy(), 1.0);
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// Rows and columns of the matrix are ordered like [SVMCore::labels].
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    /// The labels of the model, in class index order.
+    labels: Vec<i32>,
+
+    /// Counts indexed by `[true class index][predicted class index]`.
+    confusion: Vec<Vec<u64>>,
+
+    /// Total number of samples.
+    count: u64,
+}
+
+impl Evaluation {
+    /// Predicts all `(features, true_label)` pairs with `svm` and collects the results.
+    ///
+    /// # Returns
+    ///
+    /// The evaluation, [Error::UnknownLabel] for the first true label the model doesn't know,
+    /// [Error::DimensionMismatch] for samples with the wrong number of features, or
+    /// [Error::NotClassifier] for regression and one-class models.
+    pub fn new<I, F>(svm: &DenseSVM, samples: I) -> Result<Evaluation, Error>
+    where
+        I: IntoIterator<Item = (F, i32)>,
+        F: AsRef<[f32]>,
+    {
+        if !svm.svm_type.is_classifier() {
+            return Err(Error::NotClassifier);
+        }
+
+        let labels = svm.labels().collect::<Vec<_>>();
+        let mut confusion = vec![vec![0; labels.len()]; labels.len()];
+        let mut count = 0;
+        let mut problem = DenseProblem::from(svm);
+
+        for (features, true_label) in samples {
+            let actual = svm.class_index_for_label(true_label).ok_or(Error::UnknownLabel { label: true_label })?;
+            let predicted_label = svm.classify_slice(&mut problem, features.as_ref())?;
+            let predicted = svm.class_index_for_label(predicted_label)?;
+
+            confusion[actual][predicted] += 1;
+            count += 1;
+        }
+
+        Ok(Evaluation { labels, confusion, count })
+    }
+
+    /// Returns the labels of the model, in the order of rows and columns of [Evaluation::confusion_matrix].
+    pub fn labels(&self) -> &[i32] { &self.labels }
+
+    /// Returns the confusion matrix, indexed by `[true class index][predicted class index]`.
+    pub fn confusion_matrix(&self) -> &[Vec<u64>] { &self.confusion }
+
+    /// Returns the number of samples evaluated.
+    pub fn count(&self) -> u64 { self.count }
+
+    /// Returns the fraction of correctly predicted samples, or `NaN` if there were none.
+    pub fn accuracy(&self) -> f64 {
+        let correct = (0 .. self.labels.len()).map(|i| self.confusion[i][i]).sum::<u64>();
+
+        correct as f64 / self.count as f64
+    }
+
+    /// Returns the fraction of samples predicted as `label` that actually were `label`.
+    ///
+    /// `None` if the model doesn't know `label`, `NaN` if it was never predicted.
+    pub fn precision(&self, label: i32) -> Option<f64> {
+        let i = self.index(label)?;
+        let predicted = self.confusion.iter().map(|row| row[i]).sum::<u64>();
+
+        Some(self.confusion[i][i] as f64 / predicted as f64)
+    }
+
+    /// Returns the fraction of samples that were `label` and also predicted as such.
+    ///
+    /// `None` if the model doesn't know `label`, `NaN` if no sample was `label`.
+    pub fn recall(&self, label: i32) -> Option<f64> {
+        let i = self.index(label)?;
+        let actual = self.confusion[i].iter().sum::<u64>();
+
+        Some(self.confusion[i][i] as f64 / actual as f64)
+    }
+
+    /// Finds the class index of `label`.
+    fn index(&self, label: i32) -> Option<usize> { self.labels.iter().position(|x| *x == label) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Evaluation;
+    use crate::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn confusion_matrix() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let low = [0.0001, 0.0001, 0.0001, 0.0001];
+        let high = [1.2974607, 1.0227317, 1.2545854, 0.0];
+
+        let evaluation = Evaluation::new(&svm, vec![(low, 21), (high, 42), (high, 21), (low, 21)])?;
+
+        assert_eq!(evaluation.labels(), &[21, 42]);
+        assert_eq!(evaluation.confusion_matrix(), &[vec![2, 1], vec![0, 1]]);
+        assert_eq!(evaluation.count(), 4);
+        assert_eq!(evaluation.accuracy(), 0.75);
+        assert_eq!(evaluation.precision(21), Some(1.0));
+        assert_eq!(evaluation.precision(42), Some(0.5));
+        assert_eq!(evaluation.recall(21), Some(2.0 / 3.0));
+        assert_eq!(evaluation.recall(42), Some(1.0));
+        assert_eq!(evaluation.recall(7), None);
+
+        match Evaluation::new(&svm, vec![(low, 21), (low, 7)]) {
+            Err(Error::UnknownLabel { label: 7 }) => {}
+            other => panic!("Expected Error::UnknownLabel, got {:?}", other),
+        }
+
+        Ok(())
+    }
+}
@@ -20,7 +20,7 @@
 //! # Features
 //!
 //! FFSVM
-//! * loads almost all [libSVM](https://github.com/cjlin1/libsvm) types (C-SVC, ν-SVC, ε-SVR,  ν-SVR) and kernels (linear, poly, RBF and sigmoid)
+//! * loads almost all [libSVM](https://github.com/cjlin1/libsvm) types (C-SVC, ν-SVC, ε-SVR,  ν-SVR, one-class) and kernels (linear, poly, RBF and sigmoid)
 //! * produces practically same classification results as libSVM
 //! * optimized for [SIMD](https://github.com/rust-lang/rfcs/pull/2366) and can be mixed seamlessly with [Rayon](https://github.com/rayon-rs/rayon)
 //! * written in 100% Rust, but can be loaded from any language (via FFI)
@@ -77,8 +77,18 @@
 #![warn(rust_2018_idioms, explicit_outlives_requirements)]
 #![allow(clippy::unreadable_literal)]
 
+mod assembler;
+#[cfg(feature = "testing")]
+mod cross_check;
+mod ensemble;
 mod errors;
+mod evaluation;
 mod parser;
+mod pool;
+#[cfg(feature = "test-util")]
+mod random;
+mod scaler;
+mod sketch;
 mod sparse;
 mod svm;
 mod util;
@@ -88,13 +98,26 @@ mod vectors;
 pub static SAMPLE_MODEL: &str = include_str!("sample.model");
 
 pub use crate::{
+    assembler::FeatureAssembler,
+    ensemble::{SVMEnsemble, Voting},
     errors::Error,
+    evaluation::Evaluation,
     parser::ModelFile,
+    pool::PredictPool,
+    scaler::{Scaler, StandardScaler},
+    sketch::ConfidenceSketch,
     svm::{
         core::SVMCore,
-        kernel::{KernelDense, KernelSparse, Linear, Poly, Rbf, Sigmoid},
+        kernel::{CompositeKernel, KernelDense, KernelInfo, KernelSparse, Linear, Poly, Precomputed, Rbf, Sigmoid},
         predict::Predict,
-        problem::{DenseProblem, Problem, Solution, SparseProblem},
-        DenseSVM, SVMType, SparseSVM,
+        problem::{DenseProblem, LabelSource, Problem, Solution, SparseProblem},
+        DenseSVM, KernelPrecision, PrecisionMode, ProbabilitySettings, SVMType, SparseSVM,
     },
+    vectors::Triangular,
 };
+
+#[cfg(feature = "testing")]
+pub use crate::cross_check::cross_check_representations;
+
+#[cfg(feature = "test-util")]
+pub use crate::random::RandomSvmBuilder;
@@ -1,13 +1,18 @@
 mod raw;
 
-use std::{convert::TryFrom, str};
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    io::Read,
+    str,
+};
 
 pub use self::raw::*;
 
-use pest::Parser;
+use pest::{error::LineColLocation, iterators::Pair, Parser};
 use pest_derive::Parser;
 
-use crate::errors::Error;
+use crate::{errors::Error, svm::SVMType};
 
 // Hack to make `pest` re-generate parser every time file changes.
 #[cfg(debug_assertions)]
@@ -42,12 +47,73 @@ macro_rules! convert {
     };
 }
 
+/// Returns the 1-based line number a pair starts at.
+fn line_of(pair: &Pair<'_, Rule>) -> usize { pair.as_span().start_pos().line_col().0 }
+
+/// Extracts a human readable message from an error produced while converting a line.
+fn detail(error: Error) -> String {
+    match error {
+        Error::ParsingError(detail) => detail,
+        other => format!("{:?}", other),
+    }
+}
+
+/// Returns if a line of the SV section holds a support vector, i.e., is neither empty nor a comment.
+fn is_vector_line(line: &str) -> bool {
+    let line = line.trim();
+
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// Converts a grammar error into an [Error], attributing it to either the header or the
+/// support vector section depending on where it occurred.
+fn syntax_error(input: &str, error: pest::error::Error<Rule>) -> Error {
+    let line = match error.line_col {
+        LineColLocation::Pos((line, _)) | LineColLocation::Span((line, _), _) => line,
+    };
+
+    // Line number (1-based) of the `SV` marker, all lines past it are support vectors.
+    let sv_line = input.lines().position(|l| l.trim() == "SV").map(|i| i + 1);
+
+    // A support vector line ending in `index:` was most likely cut off.
+    let dangling = input.lines().nth(line - 1).map_or(false, |l| l.trim_end().ends_with(':'));
+
+    match sv_line {
+        Some(sv_line) if line > sv_line && dangling => Error::TruncatedSupportVector { line },
+        Some(sv_line) if line > sv_line => Error::SupportVectorParse {
+            line,
+            vector_index: input.lines().take(line - 1).skip(sv_line).filter(|l| is_vector_line(l)).count(),
+            detail: format!("{}", error),
+        },
+        _ => Error::HeaderParse {
+            line,
+            detail: format!("{}", error),
+        },
+    }
+}
+
+impl ModelFile<'static> {
+    /// Reads and parses a model from any [Read] source, e.g., an archive or network stream.
+    ///
+    /// The whole model is read into memory first, but unlike `ModelFile::try_from` the result
+    /// does not borrow from the caller.
+    pub fn from_reader<R>(mut reader: R) -> Result<ModelFile<'static>, Error>
+    where
+        R: Read,
+    {
+        let mut model = String::new();
+        reader.read_to_string(&mut model)?;
+
+        Ok(ModelFile::try_from(model.as_str())?.into_owned())
+    }
+}
+
 impl<'a> TryFrom<&'a str> for ModelFile<'a> {
     type Error = Error;
 
     /// Parses a string into a SVM model
     fn try_from(input: &str) -> Result<ModelFile<'_>, Error> {
-        let parsed = LibSVMModel::parse(Rule::file, input)?.next()?;
+        let parsed = LibSVMModel::parse(Rule::file, input).map_err(|e| syntax_error(input, e))?.next()?;
 
         let mut svm_type = Option::None;
         let mut kernel_type = Option::None;
@@ -64,6 +130,10 @@ impl<'a> TryFrom<&'a str> for ModelFile<'a> {
 
         let mut vectors = Vec::new();
 
+        // Line number of the first empty line found past the `SV` line, if any.
+        let mut in_sv_section = false;
+        let mut empty_sv_line = Option::None;
+
         for line in parsed.into_inner() {
             match line.as_rule() {
                 // svm_type c_svc
@@ -78,53 +148,76 @@ impl<'a> TryFrom<&'a str> for ModelFile<'a> {
                 // nr_sv 50 56 17 11 7 12
                 // SV
                 Rule::line_multiple => {
+                    let line_number = line_of(&line);
                     let mut line_pairs = line.into_inner();
-                    match next!(line_pairs, str) {
-                        "svm_type" => svm_type = Some(next!(line_pairs, str)),
-                        "kernel_type" => kernel_type = Some(next!(line_pairs, str)),
-                        "gamma" => gamma = Some(next!(line_pairs, f32)),
-                        "coef0" => coef0 = Some(next!(line_pairs, f32)),
-                        "degree" => degree = Some(next!(line_pairs, u32)),
-                        "nr_class" => nr_class = Some(next!(line_pairs, u32)),
-                        "total_sv" => total_sv = Some(next!(line_pairs, u32)),
-                        "rho" => {
-                            while let Some(x) = line_pairs.next() {
-                                rho.push(convert!(x, f64))
+
+                    let mut parse_header = || -> Result<(), Error> {
+                        match next!(line_pairs, str) {
+                            "svm_type" => {
+                                let value = next!(line_pairs, str);
+                                value.parse::<SVMType>()?;
+                                svm_type = Some(Cow::Borrowed(value));
                             }
-                        }
-                        "label" => {
-                            while let Some(x) = line_pairs.next() {
-                                label.push(convert!(x, u32))
+                            "kernel_type" => kernel_type = Some(Cow::Borrowed(next!(line_pairs, str))),
+                            "gamma" => gamma = Some(next!(line_pairs, f64)),
+                            "coef0" => coef0 = Some(next!(line_pairs, f64)),
+                            "degree" => degree = Some(next!(line_pairs, u32)),
+                            "nr_class" => nr_class = Some(next!(line_pairs, u32)),
+                            "total_sv" => total_sv = Some(next!(line_pairs, u32)),
+                            "rho" => {
+                                while let Some(x) = line_pairs.next() {
+                                    rho.push(convert!(x, f64))
+                                }
                             }
-                        }
-                        "nr_sv" => {
-                            while let Some(x) = line_pairs.next() {
-                                nr_sv.push(convert!(x, u32))
+                            "label" => {
+                                while let Some(x) = line_pairs.next() {
+                                    label.push(convert!(x, i32))
+                                }
                             }
-                        }
-                        "probA" => {
-                            let mut v = Vec::<f64>::new();
-                            while let Some(x) = line_pairs.next() {
-                                v.push(convert!(x, f64))
+                            "nr_sv" => {
+                                while let Some(x) = line_pairs.next() {
+                                    nr_sv.push(convert!(x, u32))
+                                }
                             }
-                            prob_a = Option::Some(v);
-                        }
-                        "probB" => {
-                            let mut v = Vec::<f64>::new();
-                            while let Some(x) = line_pairs.next() {
-                                v.push(convert!(x, f64))
+                            "probA" => {
+                                let mut v = Vec::<f64>::new();
+                                while let Some(x) = line_pairs.next() {
+                                    v.push(convert!(x, f64))
+                                }
+                                prob_a = Option::Some(v);
                             }
-                            prob_b = Option::Some(v);
-                        }
-                        "SV" => (),
-                        unknown => panic!("Unknown header `{}`!", unknown),
+                            "probB" => {
+                                let mut v = Vec::<f64>::new();
+                                while let Some(x) = line_pairs.next() {
+                                    v.push(convert!(x, f64))
+                                }
+                                prob_b = Option::Some(v);
+                            }
+                            "SV" => in_sv_section = true,
+                            unknown => return Err(Error::ParsingError(format!("Unknown header `{}`", unknown))),
+                        };
+
+                        Ok(())
                     };
+
+                    parse_header().map_err(|e| Error::HeaderParse {
+                        line: line_number,
+                        detail: detail(e),
+                    })?;
                 }
 
                 // 0.0625 0:0.6619648 1:0.8464851 2:0.4801146 3:0 4:0 5:0.02131653 6:0 7:0 8:0 9:0 10:0 11:0 12:0 13:0 14:0 15:0.5579834 16:0.1106567 17:0 18:0 19:0 20:0
                 // 0.0625 0:0.5861949 1:0.5556895 2:0.619291 3:0 4:0 5:0 6:0 7:0 8:0 9:0 10:0 11:0.5977631 12:0 13:0 14:0 15:0.6203156 16:0 17:0 18:0 19:0.1964417 20:0
                 // 0.0625 0:0.44675 1:0.4914977 2:0.4227562 3:0.2904663 4:0.2904663 5:0.268158 6:0 7:0 8:0 9:0 10:0 11:0.6202393 12:0.0224762 13:0 14:0 15:0.6427917 16:0.0224762 17:0 18:0 19:0.1739655 20:0
                 Rule::line_sv => {
+                    let line_number = line_of(&line);
+
+                    // Empty lines are only fine at the very end of the file.
+                    if let Some(empty_line) = empty_sv_line {
+                        return Err(Error::TruncatedSupportVector { line: empty_line });
+                    }
+
+                    let vector_index = vectors.len();
                     let line_pairs = line.into_inner();
 
                     let mut sv = SupportVector {
@@ -132,23 +225,43 @@ impl<'a> TryFrom<&'a str> for ModelFile<'a> {
                         features: Vec::new(),
                     };
 
-                    for element in line_pairs {
-                        match element.as_rule() {
-                            Rule::sv => {
-                                let mut sv_pairs = element.into_inner();
-                                let index = next!(sv_pairs, u32);
-                                let value = next!(sv_pairs, f32);
+                    let parse_vector = || -> Result<(), Error> {
+                        for element in line_pairs {
+                            match element.as_rule() {
+                                Rule::sv => {
+                                    let mut sv_pairs = element.into_inner();
+                                    let index = next!(sv_pairs, u32);
+                                    let value = next!(sv_pairs, f64);
 
-                                sv.features.push(Attribute { index, value })
+                                    sv.features.push(Attribute { index, value })
+                                }
+                                Rule::number => sv.coefs.push(convert!(element, f64)),
+                                Rule::EOI => {}
+                                _ => unreachable!(),
                             }
-                            Rule::number => sv.coefs.push(convert!(element, f32)),
-                            Rule::EOI => {}
-                            _ => unreachable!(),
                         }
+
+                        Ok(())
+                    };
+
+                    parse_vector().map_err(|e| Error::SupportVectorParse {
+                        line: line_number,
+                        vector_index,
+                        detail: detail(e),
+                    })?;
+
+                    if sv.features.is_empty() {
+                        return Err(Error::TruncatedSupportVector { line: line_number });
                     }
 
                     vectors.push(sv);
                 }
+                Rule::line_empty => {
+                    if in_sv_section && empty_sv_line.is_none() {
+                        empty_sv_line = Some(line_of(&line));
+                    }
+                }
+                Rule::EOI => {}
                 _ => unreachable!(),
             };
         }
@@ -172,3 +285,150 @@ impl<'a> TryFrom<&'a str> for ModelFile<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn comments_and_blank_lines() -> Result<(), Error> {
+        let commented = format!(
+            "# Trained 2018-10-01\n\n{}\n  # Support vectors follow\n\n{}\n# last SV\n{}\n\n\n# EOF",
+            SAMPLE_MODEL.lines().take(7).collect::<Vec<_>>().join("\n"),
+            SAMPLE_MODEL.lines().skip(7).take(2).collect::<Vec<_>>().join("\n"),
+            SAMPLE_MODEL.lines().skip(9).collect::<Vec<_>>().join("\n"),
+        );
+
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let svm_commented = DenseSVM::try_from(commented.as_str())?;
+
+        assert_eq!(svm.to_string(), svm_commented.to_string());
+
+        // Attributes must still be complete within a line.
+        assert!(ModelFile::try_from(SAMPLE_MODEL.replace("3:0.0001", "3:# 0.0001").as_str()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_newline_is_optional() -> Result<(), Error> {
+        let without_newline = SAMPLE_MODEL.trim_end();
+        let with_newline = format!("{}\n", without_newline);
+
+        let model_without = ModelFile::try_from(without_newline)?;
+        let model_with = ModelFile::try_from(with_newline.as_str())?;
+
+        assert_eq!(model_without.header.total_sv, model_with.header.total_sv);
+        assert_eq!(model_without.vectors.len(), model_without.header.total_sv as usize);
+        assert_eq!(model_with.vectors.len(), model_with.header.total_sv as usize);
+
+        let svm_without = DenseSVM::try_from(&model_without)?;
+        let svm_with = DenseSVM::try_from(&model_with)?;
+
+        let mut problem_without = Problem::from(&svm_without);
+        let mut problem_with = Problem::from(&svm_with);
+
+        problem_without.features().as_slice_mut().clone_from_slice(&[1.2974607, 1.0227317, 1.2545854, 0.0]);
+        problem_with.features().as_slice_mut().clone_from_slice(&[1.2974607, 1.0227317, 1.2545854, 0.0]);
+
+        svm_without.predict_value(&mut problem_without)?;
+        svm_with.predict_value(&mut problem_with)?;
+
+        assert_eq!(problem_without.solution(), problem_with.solution());
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_reader() -> Result<(), Error> {
+        let bytes = include_bytes!("../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+
+        let model = ModelFile::from_reader(std::io::Cursor::new(&bytes[..]))?;
+        let svm = DenseSVM::from_reader(std::io::Cursor::new(&bytes[..]))?;
+
+        assert_eq!(model.header.total_sv, 32);
+        assert_eq!(svm.classes(), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn header_errors_report_line() {
+        let model = SAMPLE_MODEL.replace("rho -0.25527", "rho -0.2.5527");
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::HeaderParse { line, .. }) => assert_eq!(line, 5),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_svm_type_reports_line() {
+        let model = SAMPLE_MODEL.replace("svm_type c_svc", "svm_type c_svr");
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::HeaderParse { line, detail }) => {
+                assert_eq!(line, 1);
+                assert!(detail.contains("c_svr"));
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn support_vector_errors_report_line_and_index() {
+        let model = SAMPLE_MODEL.replace("0:1.2974607", "0:1.29.74607");
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::SupportVectorParse { line, vector_index, .. }) => {
+                assert_eq!(line, 10);
+                assert_eq!(vector_index, 1);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn syntax_errors_skip_comments_in_vector_index() {
+        let model = SAMPLE_MODEL.replace("-1 0:1.2974607", "# Second vector\n-1 0:1.2974607 x");
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::SupportVectorParse { line, vector_index, .. }) => {
+                assert_eq!(line, 11);
+                assert_eq!(vector_index, 1);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_support_vector_line() {
+        let model = SAMPLE_MODEL.replace("\n-1 0:1.2974607", "\n\n-1 0:1.2974607");
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::TruncatedSupportVector { line }) => assert_eq!(line, 10),
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        // Trailing empty lines are fine.
+        assert!(ModelFile::try_from(format!("{}\n\n", SAMPLE_MODEL).as_str()).is_ok());
+    }
+
+    #[test]
+    fn dangling_support_vector_index() {
+        let model = format!("{} 4:", SAMPLE_MODEL.trim_end());
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::TruncatedSupportVector { line }) => assert_eq!(line, SAMPLE_MODEL.trim_end().lines().count()),
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let model = SAMPLE_MODEL.replace("3:0.0001\n", "3:\n");
+
+        match ModelFile::try_from(model.as_str()) {
+            Err(Error::TruncatedSupportVector { line }) => assert_eq!(line, 9),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+}
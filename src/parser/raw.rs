@@ -1,4 +1,8 @@
 use rand::Rng;
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Parsing result of a model file used to instantiate a [SVM].
 ///
@@ -50,65 +54,132 @@ use rand::Rng;
 ///
 #[doc(hidden)]
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ModelFile<'a> {
     crate header: Header<'a>,
     crate vectors: Vec<SupportVector>,
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Header<'a> {
-    crate svm_type: &'a str,
-    crate kernel_type: &'a str,
-    crate gamma: Option<f32>,
-    crate coef0: Option<f32>,
+    crate svm_type: Cow<'a, str>,
+    crate kernel_type: Cow<'a, str>,
+    crate gamma: Option<f64>,
+    crate coef0: Option<f64>,
     crate degree: Option<u32>,
     crate nr_class: u32,
     crate total_sv: u32,
     crate rho: Vec<f64>,
-    crate label: Vec<u32>,
+    crate label: Vec<i32>,
     crate prob_a: Option<Vec<f64>>,
     crate prob_b: Option<Vec<f64>>,
     crate nr_sv: Vec<u32>,
 }
 
 #[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 crate struct Attribute {
     crate index: u32,
-    crate value: f32,
+    crate value: f64,
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 crate struct SupportVector {
-    pub coefs: Vec<f32>,
+    pub coefs: Vec<f64>,
     pub features: Vec<Attribute>,
 }
 
 impl<'a> ModelFile<'a> {
+    /// Converts this model into one that does not borrow from the parsed string anymore.
+    pub fn into_owned(self) -> ModelFile<'static> {
+        ModelFile {
+            header: Header {
+                svm_type: Cow::Owned(self.header.svm_type.into_owned()),
+                kernel_type: Cow::Owned(self.header.kernel_type.into_owned()),
+                gamma: self.header.gamma,
+                coef0: self.header.coef0,
+                degree: self.header.degree,
+                nr_class: self.header.nr_class,
+                total_sv: self.header.total_sv,
+                rho: self.header.rho,
+                label: self.header.label,
+                prob_a: self.header.prob_a,
+                prob_b: self.header.prob_b,
+                nr_sv: self.header.nr_sv,
+            },
+            vectors: self.vectors,
+        }
+    }
+
     pub fn random_dense<'b>(svm_type: &'b str, kernel_type: &'b str, total_sv: u32, attr: u32) -> ModelFile<'b> {
-        let mut rng = rand::thread_rng();
+        Self::random_dense_with_rng(&mut rand::thread_rng(), svm_type, kernel_type, total_sv, attr)
+    }
 
+    /// Like [ModelFile::random_dense], but draws all values from `rng`, so a seeded `rng` always
+    /// produces the same model.
+    pub fn random_dense_with_rng<'b, R: Rng>(rng: &mut R, svm_type: &'b str, kernel_type: &'b str, total_sv: u32, attr: u32) -> ModelFile<'b> {
         ModelFile {
             header: Header {
-                svm_type,
-                kernel_type,
+                svm_type: svm_type.into(),
+                kernel_type: kernel_type.into(),
                 total_sv,
-                gamma: Some(rng.gen::<f32>()),
-                coef0: Some(rng.gen::<f32>()),
+                gamma: Some(rng.gen::<f64>()),
+                coef0: Some(rng.gen::<f64>()),
                 degree: Some(rng.gen_range(1, 10)),
                 nr_class: 2,
                 rho: vec![rng.gen::<f64>()],
                 label: vec![0, 1],
-                prob_a: Some(vec![rng.gen::<f64>(), rng.gen::<f64>()]),
-                prob_b: Some(vec![rng.gen::<f64>(), rng.gen::<f64>()]),
+                prob_a: Some(vec![rng.gen::<f64>()]),
+                prob_b: Some(vec![rng.gen::<f64>()]),
                 nr_sv: vec![total_sv / 2, total_sv / 2],
             },
             vectors: (0 .. total_sv)
                 .map(|_| SupportVector {
-                    coefs: vec![rng.gen::<f32>()],
+                    coefs: vec![rng.gen::<f64>()],
+                    features: (0 .. attr)
+                        .map(|i| Attribute {
+                            index: i,
+                            value: rng.gen::<f64>(),
+                        }).collect(),
+                }).collect(),
+        }
+    }
+
+    /// Creates a random dense model with `nr_class` classes of `sv_per_class` support vectors
+    /// each, e.g., for benchmarks and tests that need more than two classes.
+    ///
+    /// Labels are `0 .. nr_class`, and there are `attr` attributes per support vector. All
+    /// other values (kernel parameters, `rho`, probability parameters, coefficients and
+    /// attribute values) are random and differ on every call.
+    pub fn random_dense_multiclass<'b>(svm_type: &'b str, kernel_type: &'b str, nr_class: u32, sv_per_class: u32, attr: u32) -> ModelFile<'b> {
+        let mut rng = rand::thread_rng();
+        let num_pairs = (nr_class * (nr_class - 1) / 2) as usize;
+        let total_sv = nr_class * sv_per_class;
+
+        ModelFile {
+            header: Header {
+                svm_type: svm_type.into(),
+                kernel_type: kernel_type.into(),
+                total_sv,
+                gamma: Some(rng.gen::<f64>()),
+                coef0: Some(rng.gen::<f64>()),
+                degree: Some(rng.gen_range(1, 10)),
+                nr_class,
+                rho: (0 .. num_pairs).map(|_| rng.gen::<f64>()).collect(),
+                label: (0 .. nr_class as i32).collect(),
+                prob_a: Some((0 .. num_pairs).map(|_| -rng.gen::<f64>()).collect()),
+                prob_b: Some((0 .. num_pairs).map(|_| rng.gen::<f64>()).collect()),
+                nr_sv: vec![sv_per_class; nr_class as usize],
+            },
+            vectors: (0 .. total_sv)
+                .map(|_| SupportVector {
+                    coefs: (1 .. nr_class).map(|_| rng.gen::<f64>() - 0.5).collect(),
                     features: (0 .. attr)
                         .map(|i| Attribute {
                             index: i,
-                            value: rng.gen::<f32>(),
+                            value: rng.gen::<f64>(),
                         }).collect(),
                 }).collect(),
         }
@@ -0,0 +1,104 @@
+use crate::{
+    errors::Error,
+    svm::{problem::DenseProblem, DenseSVM},
+};
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Shares a [DenseSVM] between threads and recycles the [Problem]s used for predicting.
+///
+/// # Description
+///
+/// Allocating a [Problem] per prediction is comparatively expensive for small models. A
+/// `PredictPool` keeps finished problems around and hands them out again, so services that
+/// classify one sample per request don't need to manage problems themselves:
+///
+/// ```
+/// #![feature(try_from)]
+///
+/// use ffsvm::*;
+/// use std::{convert::TryFrom, sync::Arc};
+///
+/// fn main() -> Result<(), Error> {
+///     let pool = Arc::new(PredictPool::new(Arc::new(DenseSVM::try_from(SAMPLE_MODEL)?)));
+///
+///     // Clone `pool` into as many threads as needed.
+///     assert_eq!(pool.predict_label(&[0.23122, 0.0, 0.0, 0.0])?, 42);
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// Problems are only created when all existing ones are in use, so the pool grows to the
+/// highest number of concurrent predictions and stays there.
+pub struct PredictPool {
+    /// The shared model.
+    svm: Arc<DenseSVM>,
+
+    /// Problems not currently in use.
+    problems: Mutex<Vec<DenseProblem>>,
+}
+
+impl PredictPool {
+    /// Creates a new, empty pool for the given SVM.
+    pub fn new(svm: Arc<DenseSVM>) -> PredictPool {
+        PredictPool {
+            svm,
+            problems: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the SVM predictions are made with.
+    pub fn svm(&self) -> &Arc<DenseSVM> { &self.svm }
+
+    /// Returns the number of problems currently idle in the pool.
+    pub fn idle(&self) -> usize { self.lock().len() }
+
+    /// Classifies the given features with a pooled [Problem], see [DenseSVM::classify_slice].
+    ///
+    /// # Returns
+    ///
+    /// The predicted label, [Error::DimensionMismatch] if the number of features does not match
+    /// the model, or [Error::NotClassifier] for regression and one-class models.
+    pub fn predict_label(&self, features: &[f32]) -> Result<i32, Error> {
+        let pooled = self.lock().pop();
+        let mut problem = pooled.unwrap_or_else(|| DenseProblem::from(self.svm.as_ref()));
+
+        let result = self.svm.classify_slice(&mut problem, features);
+
+        self.lock().push(problem);
+
+        result
+    }
+
+    /// Locks the idle problems. A panic while holding the lock can't leave the `Vec` in an
+    /// inconsistent state, so poisoning is ignored.
+    fn lock(&self) -> MutexGuard<'_, Vec<DenseProblem>> { self.problems.lock().unwrap_or_else(PoisonError::into_inner) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PredictPool;
+    use crate::*;
+    use std::{convert::TryFrom, sync::Arc, thread};
+
+    #[test]
+    fn predicts_from_many_threads() -> Result<(), Error> {
+        let pool = Arc::new(PredictPool::new(Arc::new(DenseSVM::try_from(SAMPLE_MODEL)?)));
+
+        let threads = (0 .. 4)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || (0 .. 100).map(|_| pool.predict_label(&[0.23122, 0.0, 0.0, 0.0])).collect::<Result<Vec<_>, _>>())
+            }).collect::<Vec<_>>();
+
+        for thread in threads {
+            assert!(thread.join().unwrap()?.iter().all(|label| *label == 42));
+        }
+
+        assert!(pool.idle() >= 1 && pool.idle() <= 4);
+        assert!(pool.predict_label(&[0.0]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    errors::Error,
+    parser::{Attribute, Header, ModelFile, SupportVector},
+    svm::{kernel::KernelInfo, DenseSVM, SVMType},
+};
+
+use rand::Rng;
+use std::convert::TryFrom;
+
+/// Builds random [DenseSVM]s, e.g., for fuzz or property testing of code using this crate.
+///
+/// # Description
+///
+/// All numeric model data (support vectors, coefficients, `rho`, probabilities) is random, only
+/// the shape of the model is controlled by the builder. Such models don't predict anything
+/// meaningful, but exercise the same code paths a trained model would. Only available with the
+/// `test-util` feature.
+///
+/// ```
+/// use ffsvm::*;
+///
+/// fn main() -> Result<(), Error> {
+///     let svm = RandomSvmBuilder::new(SVMType::ESvr)
+///         .kernel(KernelInfo::Linear)
+///         .support_vectors(64)
+///         .attributes(16)
+///         .build()?;
+///
+///     let mut problem = Problem::from(&svm);
+///     svm.predict_value(&mut problem)?;
+///
+///     assert_eq!(svm.attributes(), 16);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RandomSvmBuilder {
+    svm_type: SVMType,
+    kernel: KernelInfo,
+    classes: usize,
+    support_vectors: usize,
+    attributes: usize,
+    probabilities: bool,
+}
+
+impl RandomSvmBuilder {
+    /// Creates a builder for the given model type, by default an RBF model with 2 classes,
+    /// 16 support vectors (per class) and 8 attributes.
+    pub fn new(svm_type: SVMType) -> RandomSvmBuilder {
+        RandomSvmBuilder {
+            svm_type,
+            kernel: KernelInfo::Rbf { gamma: 0.5 },
+            classes: 2,
+            support_vectors: 16,
+            attributes: 8,
+            probabilities: false,
+        }
+    }
+
+    /// Sets the kernel family and its parameters. [KernelInfo::Custom] is not supported.
+    pub fn kernel(mut self, kernel: KernelInfo) -> Self {
+        self.kernel = kernel;
+        self
+    }
+
+    /// Sets the number of classes, only used for C-SVC and ν-SVC models.
+    pub fn classes(mut self, classes: usize) -> Self {
+        self.classes = classes;
+        self
+    }
+
+    /// Sets the number of support vectors per class, or in total for regression and one-class models.
+    pub fn support_vectors(mut self, support_vectors: usize) -> Self {
+        self.support_vectors = support_vectors;
+        self
+    }
+
+    /// Sets the number of attributes.
+    pub fn attributes(mut self, attributes: usize) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Sets if the model has probability estimates. Ignored for one-class models.
+    pub fn probabilities(mut self, probabilities: bool) -> Self {
+        self.probabilities = probabilities;
+        self
+    }
+
+    /// Creates the random model as a [ModelFile].
+    ///
+    /// # Returns
+    ///
+    /// The model, or [Error::CustomKernel] if the kernel is [KernelInfo::Custom].
+    pub fn build_model(&self) -> Result<ModelFile<'static>, Error> {
+        if self.kernel == KernelInfo::Custom {
+            return Err(Error::CustomKernel);
+        }
+
+        let mut rng = rand::thread_rng();
+        let (gamma, coef0, degree) = self.kernel.parameters();
+
+        let is_classifier = self.svm_type.is_classifier();
+        let classes = if is_classifier { self.classes.max(2) } else { 2 };
+        let num_pairs = classes * (classes - 1) / 2;
+        let num_coefficients = if is_classifier { classes - 1 } else { 1 };
+        let total_sv = if is_classifier { classes * self.support_vectors } else { self.support_vectors };
+
+        let (prob_a, prob_b) = match (self.probabilities, self.svm_type) {
+            (false, _) | (true, SVMType::OneClass) => (None, None),
+            (true, SVMType::ESvr) | (true, SVMType::NuSvr) => (Some(vec![rng.gen::<f64>()]), None),
+            (true, _) => (
+                Some((0 .. num_pairs).map(|_| -rng.gen::<f64>()).collect()),
+                Some((0 .. num_pairs).map(|_| rng.gen::<f64>()).collect()),
+            ),
+        };
+
+        Ok(ModelFile {
+            header: Header {
+                svm_type: self.svm_type.to_string().into(),
+                kernel_type: self.kernel.to_string().into(),
+                gamma,
+                coef0,
+                degree,
+                nr_class: classes as u32,
+                total_sv: total_sv as u32,
+                rho: (0 .. if is_classifier { num_pairs } else { 1 }).map(|_| rng.gen::<f64>()).collect(),
+                label: if is_classifier { (0 .. classes as i32).collect() } else { Vec::new() },
+                prob_a,
+                prob_b,
+                nr_sv: if is_classifier { vec![self.support_vectors as u32; classes] } else { Vec::new() },
+            },
+            vectors: (0 .. total_sv)
+                .map(|_| SupportVector {
+                    coefs: (0 .. num_coefficients).map(|_| rng.gen::<f64>() - 0.5).collect(),
+                    features: (0 .. self.attributes as u32)
+                        .map(|index| Attribute {
+                            index,
+                            value: rng.gen::<f64>(),
+                        }).collect(),
+                }).collect(),
+        })
+    }
+
+    /// Creates the random SVM.
+    ///
+    /// # Returns
+    ///
+    /// The SVM, or [Error::CustomKernel] if the kernel is [KernelInfo::Custom].
+    pub fn build(&self) -> Result<DenseSVM, Error> { DenseSVM::try_from(&self.build_model()?) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomSvmBuilder;
+    use crate::*;
+
+    #[test]
+    fn builds_all_types() -> Result<(), Error> {
+        for svm_type in &[SVMType::CSvc, SVMType::NuSvc, SVMType::ESvr, SVMType::NuSvr, SVMType::OneClass] {
+            for kernel in &[KernelInfo::Linear, KernelInfo::Rbf { gamma: 0.1 }, KernelInfo::Sigmoid { gamma: 0.1, coef0: 0.5 }] {
+                let svm = RandomSvmBuilder::new(*svm_type).kernel(*kernel).classes(3).support_vectors(8).attributes(5).probabilities(true).build()?;
+                let mut problem = Problem::from(&svm);
+
+                svm.predict_value(&mut problem)?;
+
+                assert_eq!(svm.attributes(), 5);
+                assert_eq!(svm.kernel_info(), *kernel);
+
+                match (svm_type, problem.solution()) {
+                    (SVMType::CSvc, Solution::Label(_)) | (SVMType::NuSvc, Solution::Label(_)) => assert_eq!(svm.classes(), 3),
+                    (SVMType::ESvr, Solution::Value(_)) | (SVMType::NuSvr, Solution::Value(_)) => {}
+                    (SVMType::OneClass, Solution::Inlier(_)) => {}
+                    (_, solution) => panic!("Unexpected solution {:?} for {:?}", solution, svm_type),
+                }
+            }
+        }
+
+        match RandomSvmBuilder::new(SVMType::CSvc).kernel(KernelInfo::Custom).build() {
+            Err(Error::CustomKernel) => {}
+            other => panic!("Expected Error::CustomKernel, got {:?}", other.map(|_| ())),
+        }
+
+        Ok(())
+    }
+}
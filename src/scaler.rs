@@ -0,0 +1,361 @@
+use crate::errors::Error;
+use std::{convert::TryFrom, io::Read};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Scales features like libSVM's `svm-scale`, using the bounds from a `.range` file.
+///
+/// # Description
+///
+/// Models trained on data scaled with `svm-scale` expect features scaled the same way. If you
+/// saved the scaling parameters with `svm-scale -s file.range`, you can load that file and apply
+/// it to your features before predicting:
+///
+/// ```
+/// #![feature(try_from)]
+///
+/// use ffsvm::*;
+/// use std::convert::TryFrom;
+///
+/// fn main() -> Result<(), Error> {
+///     let scaler = Scaler::try_from("x\n-1 1\n1 0 10\n2 -5 5\n")?;
+///     let mut features = [5.0, 10.0];
+///
+///     scaler.scale(&mut features);
+///
+///     assert_eq!(features, [0.0, 2.0]);
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// With a [Problem] you would call `scaler.scale(problem.features().as_slice_mut())`.
+///
+/// # Edge Cases
+///
+/// * Values outside the `[min, max]` seen during training are extrapolated linearly and not
+///   clamped, just like `svm-scale` does.
+/// * Features with `min == max` (no variance during training) are set to `0.0`. `svm-scale`
+///   omits them from its output, which libSVM treats as `0.0`.
+/// * Features without an entry in the range file are set to `0.0` for the same reason.
+/// * The optional `y` section for regression targets is accepted but ignored.
+///
+/// `svm-scale` writes the feature indices of its input, which are 1-based for regular libSVM
+/// data. [TryFrom] therefore expects 1-based indices and scales feature `i` with the bounds of
+/// index `i + 1`, matching how [TryFrom] of [DenseSVM] places attribute `1` at feature `0`. For
+/// range files with 0-based indices, or models loaded with [DenseSVM::try_from_indexed], use
+/// [Scaler::try_from_indexed] instead.
+#[derive(Clone, Debug)]
+pub struct Scaler {
+    /// Lower end of the target range.
+    lower: f64,
+
+    /// Upper end of the target range.
+    upper: f64,
+
+    /// The `(min, max)` bounds per feature index, `None` for features not in the range file.
+    bounds: Vec<Option<(f64, f64)>>,
+}
+
+impl Scaler {
+    /// Parses a `.range` file, scaling feature `i` with the bounds of index `i`.
+    ///
+    /// Use this for range files with 0-based indices, or for 1-based range files whose model was
+    /// loaded with [DenseSVM::try_from_indexed], where feature `0` is unused.
+    pub fn try_from_indexed(range: &str) -> Result<Scaler, Error> { Scaler::parse(range, 0) }
+
+    /// Reads and parses a 1-based `.range` file from any [Read] source, see [TryFrom].
+    pub fn from_reader<R>(mut reader: R) -> Result<Scaler, Error>
+    where
+        R: Read,
+    {
+        let mut range = String::new();
+        reader.read_to_string(&mut range)?;
+
+        Scaler::try_from(range.as_str())
+    }
+
+    /// Returns the target range `(lower, upper)` features are scaled into.
+    pub fn target_range(&self) -> (f64, f64) { (self.lower, self.upper) }
+
+    /// Returns the `(min, max)` bounds of feature `index` (a position in the features passed to
+    /// [Scaler::scale]), if the range file had an entry for it.
+    pub fn bounds(&self, index: usize) -> Option<(f64, f64)> { self.bounds.get(index).cloned().unwrap_or(None) }
+
+    /// Scales `features` in place, see the type documentation for edge cases.
+    pub fn scale(&self, features: &mut [f32]) {
+        for (index, feature) in features.iter_mut().enumerate() {
+            *feature = match self.bounds(index) {
+                Some((min, max)) if min != max => {
+                    let value = f64::from(*feature);
+
+                    if value == min {
+                        self.lower as f32
+                    } else if value == max {
+                        self.upper as f32
+                    } else {
+                        (self.lower + (self.upper - self.lower) * (value - min) / (max - min)) as f32
+                    }
+                }
+                _ => 0.0,
+            };
+        }
+    }
+}
+
+/// Parses all numbers of a line, reporting the 1-based `line` on failure.
+fn numbers(line: usize, text: &str) -> Result<Vec<f64>, Error> {
+    text.split_whitespace()
+        .map(|x| x.parse::<f64>().map_err(|_| Error::ParsingError(format!("Invalid number `{}` in line {}", x, line))))
+        .collect()
+}
+
+impl Scaler {
+    /// Parses a `.range` file, storing the bounds of index `first_index` at feature `0`.
+    fn parse(input: &str, first_index: usize) -> Result<Scaler, Error> {
+        let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l.trim())).filter(|(_, l)| !l.is_empty()).peekable();
+
+        // Skip the target section, `y` followed by the target range and the observed range.
+        if let Some((_, "y")) = lines.peek() {
+            lines.next();
+            lines.next();
+            lines.next();
+        }
+
+        match lines.next() {
+            Some((_, "x")) => {}
+            Some((line, other)) => return Err(Error::ParsingError(format!("Expected `x` but found `{}` in line {}", other, line))),
+            None => return Err(Error::ParsingError("Range file is empty".to_owned())),
+        }
+
+        let (lower, upper) = match lines.next() {
+            Some((line, text)) => match numbers(line, text)?.as_slice() {
+                [lower, upper] => (*lower, *upper),
+                _ => return Err(Error::ParsingError(format!("Expected `lower upper` in line {}", line))),
+            },
+            None => return Err(Error::ParsingError("Range file has no target range".to_owned())),
+        };
+
+        let mut bounds = Vec::new();
+
+        for (line, text) in lines {
+            let (index, min, max) = match numbers(line, text)?.as_slice() {
+                [index, min, max] if *index >= first_index as f64 && index.fract() == 0.0 => (*index as usize - first_index, *min, *max),
+                [index, _, _] if *index == 0.0 => return Err(Error::ParsingError(format!("Index 0 in 1-based range file in line {}", line))),
+                _ => return Err(Error::ParsingError(format!("Expected `index min max` in line {}", line))),
+            };
+
+            if index >= bounds.len() {
+                bounds.resize(index + 1, None);
+            }
+
+            bounds[index] = Some((min, max));
+        }
+
+        Ok(Scaler { lower, upper, bounds })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Scaler {
+    type Error = Error;
+
+    /// Parses the content of a libSVM `.range` file with 1-based indices, as written by `svm-scale -s`.
+    fn try_from(input: &str) -> Result<Scaler, Error> { Scaler::parse(input, 1) }
+}
+
+/// Standardizes features to zero mean and unit standard deviation (z-scores).
+///
+/// # Description
+///
+/// Fit the scaler once on your training data, use it to transform the training data before
+/// calling `svm-train`, and persist it alongside the model (with the `serde` feature enabled) to
+/// transform features the same way at prediction time:
+///
+/// ```
+/// use ffsvm::*;
+///
+/// fn main() -> Result<(), Error> {
+///     let scaler = StandardScaler::fit(&[&[1.0, 10.0], &[3.0, 10.0]])?;
+///     let mut features = [2.0, 12.0];
+///
+///     scaler.transform(&mut features)?;
+///
+///     assert_eq!(features, [0.0, 0.0]);
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// Features that had a standard deviation of `0.0` during fitting carry no information and
+/// are always set to `0.0`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StandardScaler {
+    /// Mean of each feature.
+    means: Vec<f64>,
+
+    /// Population standard deviation of each feature.
+    std_devs: Vec<f64>,
+}
+
+impl StandardScaler {
+    /// Computes means and standard deviations of each feature over all `rows`.
+    ///
+    /// Returns [Error::DimensionMismatch] unless all rows have the same length.
+    pub fn fit(rows: &[&[f32]]) -> Result<StandardScaler, Error> {
+        let num_features = rows.first().map_or(0, |row| row.len());
+        let mut means = vec![0.0; num_features];
+        let mut std_devs = vec![0.0; num_features];
+
+        for row in rows {
+            if row.len() != num_features {
+                return Err(Error::DimensionMismatch {
+                    expected: num_features,
+                    actual: row.len(),
+                });
+            }
+
+            for (mean, x) in means.iter_mut().zip(row.iter()) {
+                *mean += f64::from(*x) / rows.len() as f64;
+            }
+        }
+
+        for row in rows {
+            for ((std_dev, mean), x) in std_devs.iter_mut().zip(means.iter()).zip(row.iter()) {
+                *std_dev += (f64::from(*x) - mean).powi(2) / rows.len() as f64;
+            }
+        }
+
+        for std_dev in std_devs.iter_mut() {
+            *std_dev = std_dev.sqrt();
+        }
+
+        Ok(StandardScaler { means, std_devs })
+    }
+
+    /// Returns the mean of each feature.
+    pub fn means(&self) -> &[f64] { &self.means }
+
+    /// Returns the standard deviation of each feature.
+    pub fn std_devs(&self) -> &[f64] { &self.std_devs }
+
+    /// Standardizes `features` in place.
+    ///
+    /// Returns [Error::DimensionMismatch] if `features` does not have as many features as
+    /// the data this scaler was fitted on.
+    pub fn transform(&self, features: &mut [f32]) -> Result<(), Error> {
+        if features.len() != self.means.len() {
+            return Err(Error::DimensionMismatch {
+                expected: self.means.len(),
+                actual: features.len(),
+            });
+        }
+
+        for ((feature, mean), std_dev) in features.iter_mut().zip(self.means.iter()).zip(self.std_devs.iter()) {
+            *feature = if *std_dev == 0.0 { 0.0 } else { ((f64::from(*feature) - mean) / std_dev) as f32 };
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scaler, StandardScaler};
+    use crate::{DenseSVM, Error};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn scales_like_svm_scale() -> Result<(), Error> {
+        let scaler = Scaler::try_from("y\n0 1\n2 4\nx\n-1 1\n1 0 10\n2 -5 5\n3 3 3\n")?;
+        let mut features = [2.5, 5.0, 3.0, 7.0];
+
+        scaler.scale(&mut features);
+
+        assert_eq!(scaler.target_range(), (-1.0, 1.0));
+        assert_eq!(features, [-0.5, 1.0, 0.0, 0.0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extrapolates_outside_training_range() -> Result<(), Error> {
+        let scaler = Scaler::try_from_indexed("x\n0 1\n0 0 10\n1 0 10\n")?;
+        let mut features = [-10.0, 20.0];
+
+        scaler.scale(&mut features);
+
+        assert_eq!(features, [-1.0, 2.0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(Scaler::try_from("").is_err());
+        assert!(Scaler::try_from("z\n-1 1\n").is_err());
+        assert!(Scaler::try_from("x\n-1\n").is_err());
+        assert!(Scaler::try_from("x\n-1 1\n1 0.5\n").is_err());
+        assert!(Scaler::try_from("x\n-1 1\n1 a 2\n").is_err());
+        assert!(Scaler::try_from("x\n-1 1\n0 0 1\n").is_err());
+        assert!(Scaler::try_from_indexed("x\n-1 1\n0 0 1\n").is_ok());
+    }
+
+    #[test]
+    fn scales_for_one_based_model() -> Result<(), Error> {
+        // Written by `svm-scale -s` for three 1-based features, scaled into `[-1, 1]`.
+        let range = "x\n-1 1\n1 0 10\n2 -5 5\n3 100 200\n";
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0\nlabel 1 -1\nnr_sv 1 1\nSV\n1 1:1 2:0.5 3:-1\n-1 1:-1 2:-0.5 3:1\n";
+
+        let scaler = Scaler::try_from(range)?;
+        let svm = DenseSVM::try_from(model)?;
+
+        let mut features = [10.0, 2.5, 100.0];
+        scaler.scale(&mut features);
+
+        assert_eq!(scaler.bounds(0), Some((0.0, 10.0)));
+        assert_eq!(features, [1.0, 0.5, -1.0]);
+        assert_eq!(svm.predict_label(&features)?, 1);
+
+        let mut features = [0.0, -2.5, 200.0];
+        scaler.scale(&mut features);
+
+        assert_eq!(features, [-1.0, -0.5, 1.0]);
+        assert_eq!(svm.predict_label(&features)?, -1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn standardizes() -> Result<(), Error> {
+        let scaler = StandardScaler::fit(&[&[1.0, 5.0, 2.0], &[3.0, 5.0, 4.0], &[5.0, 5.0, 6.0]])?;
+        let mut features = [5.0, 7.0, 0.0];
+
+        scaler.transform(&mut features)?;
+
+        assert_eq!(scaler.means(), &[3.0, 5.0, 4.0]);
+        assert!((features[0] - 1.2247449).abs() < 1e-6);
+        assert_eq!(features[1], 0.0);
+        assert!((features[2] + 2.4494898).abs() < 1e-6);
+
+        assert!(scaler.transform(&mut features[.. 2]).is_err());
+        assert!(StandardScaler::fit(&[&[1.0, 2.0], &[1.0]]).is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn standard_scaler_serde_round_trip() -> Result<(), Error> {
+        let scaler = StandardScaler::fit(&[&[1.0, 2.0], &[3.0, 7.0]])?;
+
+        let bytes = bincode::serialize(&scaler).unwrap();
+        let loaded: StandardScaler = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(scaler.means(), loaded.means());
+        assert_eq!(scaler.std_devs(), loaded.std_devs());
+
+        Ok(())
+    }
+}
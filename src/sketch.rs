@@ -0,0 +1,119 @@
+/// Streaming estimate of the distribution of prediction confidences.
+///
+/// # Description
+///
+/// When running a model in production it is often desirable to reject predictions the SVM
+/// is unsure about. The `ConfidenceSketch` observes the confidences of a stream of predictions
+/// (e.g., as returned by [SVMCore::predict_with_confidence]) without storing them, and can be
+/// queried for quantiles afterwards, answering questions like "below which confidence are the
+/// bottom 5% of all predictions".
+///
+/// Internally this is a fixed-size histogram over `[0, 1]`, so memory usage is constant and the
+/// estimated quantiles are accurate up to the bin width.
+///
+/// ```
+/// use ffsvm::ConfidenceSketch;
+///
+/// let mut sketch = ConfidenceSketch::default();
+///
+/// sketch.observe(0.8);
+/// sketch.observe(0.9);
+///
+/// let threshold = sketch.quantile(0.05);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConfidenceSketch {
+    /// Number of observations per bin.
+    bins: Vec<u64>,
+
+    /// Total number of observations.
+    count: u64,
+}
+
+impl ConfidenceSketch {
+    /// Creates a new sketch with the given number of histogram bins.
+    pub fn with_bins(bins: usize) -> ConfidenceSketch {
+        ConfidenceSketch {
+            bins: vec![0; bins.max(1)],
+            count: 0,
+        }
+    }
+
+    /// Records a single confidence. Values outside `[0, 1]` are clamped, `NaN` is ignored.
+    pub fn observe(&mut self, confidence: f64) {
+        if confidence.is_nan() {
+            return;
+        }
+
+        let num_bins = self.bins.len();
+        let bin = (confidence.max(0.0).min(1.0) * num_bins as f64) as usize;
+
+        self.bins[bin.min(num_bins - 1)] += 1;
+        self.count += 1;
+    }
+
+    /// Returns the number of confidences observed so far.
+    pub fn count(&self) -> u64 { self.count }
+
+    /// Estimates the `q`-quantile (with `q` in `[0, 1]`) of all observed confidences.
+    ///
+    /// Returns `NaN` if nothing was observed yet.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if self.count == 0 {
+            return std::f64::NAN;
+        }
+
+        let num_bins = self.bins.len() as f64;
+        let target = q.max(0.0).min(1.0) * self.count as f64;
+        let mut cumulative = 0.0;
+
+        for (i, bin) in self.bins.iter().enumerate() {
+            let in_bin = *bin as f64;
+
+            if in_bin > 0.0 && cumulative + in_bin >= target {
+                // Interpolate linearly within the bin.
+                let fraction = (target - cumulative) / in_bin;
+                return (i as f64 + fraction) / num_bins;
+            }
+
+            cumulative += in_bin;
+        }
+
+        1.0
+    }
+}
+
+impl Default for ConfidenceSketch {
+    fn default() -> Self { ConfidenceSketch::with_bins(1000) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfidenceSketch;
+
+    #[test]
+    fn estimates_quantiles() {
+        let mut sketch = ConfidenceSketch::default();
+
+        for i in 0 .. 10_000 {
+            sketch.observe(f64::from(i) / 10_000.0);
+        }
+
+        assert_eq!(sketch.count(), 10_000);
+        assert!((sketch.quantile(0.5) - 0.5).abs() < 0.01);
+        assert!((sketch.quantile(0.05) - 0.05).abs() < 0.01);
+    }
+
+    #[test]
+    fn estimates_skewed_median() {
+        let mut sketch = ConfidenceSketch::default();
+
+        // Squares of a uniform distribution have their median at 0.25.
+        for i in 0 .. 10_000 {
+            let x = f64::from(i) / 10_000.0;
+            sketch.observe(x * x);
+        }
+
+        assert!((sketch.quantile(0.5) - 0.25).abs() < 0.01);
+    }
+}
@@ -31,6 +31,12 @@ where
         self.entries.clear();
     }
 
+    /// Returns the number of stored (non-zero) entries.
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Returns `true` if no entries are stored.
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
     pub fn iter(&self) -> SparseVectorIter<'_, T> {
         SparseVectorIter {
             vector: self,
@@ -138,6 +144,12 @@ where
         &self.vectors[row]
     }
 
+    /// Reorders the rows so that row `i` afterwards holds what was row `order[i]` before.
+    crate fn permute_rows(&mut self, order: &[usize]) {
+        let mut vectors = std::mem::replace(&mut self.vectors, Vec::new());
+        self.vectors = order.iter().map(|&i| std::mem::replace(&mut vectors[i], SparseVector::new())).collect();
+    }
+
     #[inline]
     pub fn row_iter(&self) -> SparseMatrixIter<'_, T> {
         SparseMatrixIter {
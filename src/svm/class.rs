@@ -1,12 +1,19 @@
-use crate::sparse::SparseMatrix;
-use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix};
+use crate::{
+    parser::Attribute,
+    sparse::{SparseMatrix, SparseVector},
+    svm::{
+        kernel::{KernelDense, KernelSparse},
+        KernelPrecision,
+    },
+};
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
 
 /// Represents one class of the SVM model.
 #[derive(Clone, Debug)]
 #[doc(hidden)]
 crate struct Class<M32> {
     /// The label of this class
-    crate label: u32,
+    crate label: i32,
 
     /// The number of support vectors in this class
     crate num_support_vectors: usize,
@@ -16,28 +23,86 @@ crate struct Class<M32> {
 
     /// All support vectors in this class.
     crate support_vectors: M32,
+
+    /// For dense classes, the same support vectors with the `f64` values of the model file, used
+    /// for [KernelPrecision::Double].
+    crate support_vectors_f64: Option<SimdMatrix<f64s, RowOptimized>>,
+
+    /// Factor this class' decision contributions are scaled with, `1.0` unless changed
+    /// via [SVMCore::apply_class_frequency_correction].
+    crate correction: f64,
+
+    /// For linear kernels, one weight vector per coefficient row (i.e., `coefficients * support_vectors`),
+    /// only present once enabled via [SVMCore::enable_linear_fast].
+    crate linear_weights: Option<Vec<Vec<f64>>>,
 }
 
 impl Class<SimdMatrix<f32s, RowOptimized>> {
     /// Creates a new class with the given parameters.
-    pub fn with_parameters(classes: usize, support_vectors: usize, attributes: usize, label: u32) -> Class<SimdMatrix<f32s, RowOptimized>> {
+    pub fn with_parameters(classes: usize, support_vectors: usize, attributes: usize, label: i32) -> Class<SimdMatrix<f32s, RowOptimized>> {
         Class {
             label,
             num_support_vectors: support_vectors,
             coefficients: SimdMatrix::with_dimension(classes - 1, support_vectors),
             support_vectors: SimdMatrix::with_dimension(support_vectors, attributes),
+            support_vectors_f64: Some(SimdMatrix::with_dimension(support_vectors, attributes)),
+            correction: 1.0,
+            linear_weights: None,
+        }
+    }
+
+    /// Computes the kernel values of all support vectors of this class in the given precision.
+    crate fn compute_kernel_values(&self, kernel: &dyn KernelDense, precision: KernelPrecision, feature: &SimdVector<f32s>, output: &mut [f64], scratch: &mut Vec<f64>) {
+        match (precision, &self.support_vectors_f64) {
+            (KernelPrecision::Double, Some(vectors_f64)) => kernel.compute_f64_with_scratch(&self.support_vectors, vectors_f64, feature, output, scratch),
+            _ => kernel.compute_with_scratch(&self.support_vectors, feature, output, scratch),
         }
     }
+
+    /// Returns all attributes of support vector `i_vector`, as written to a model file.
+    crate fn dense_attributes(&self, i_vector: usize, num_attributes: usize) -> Vec<Attribute> {
+        let support_vectors = self.support_vectors.flat();
+
+        (0 .. num_attributes)
+            .map(|i_attribute| Attribute {
+                index: i_attribute as u32,
+                value: match &self.support_vectors_f64 {
+                    Some(vectors_f64) => vectors_f64.flat()[(i_vector, i_attribute)],
+                    None => f64::from(support_vectors[(i_vector, i_attribute)]),
+                },
+            }).collect()
+    }
 }
 
 impl Class<SparseMatrix<f32>> {
     /// Creates a new class with the given parameters.
-    pub fn with_parameters(classes: usize, support_vectors: usize, _attributes: usize, label: u32) -> Class<SparseMatrix<f32>> {
+    pub fn with_parameters(classes: usize, support_vectors: usize, _attributes: usize, label: i32) -> Class<SparseMatrix<f32>> {
         Class {
             label,
             num_support_vectors: support_vectors,
             coefficients: SimdMatrix::with_dimension(classes - 1, support_vectors),
             support_vectors: SparseMatrix::with(support_vectors),
+            support_vectors_f64: None,
+            correction: 1.0,
+            linear_weights: None,
+        }
+    }
+
+    /// Computes the kernel values of all support vectors of this class. Sparse kernels are
+    /// always computed in their usual precision.
+    crate fn compute_kernel_values(&self, kernel: &dyn KernelSparse, _precision: KernelPrecision, feature: &SparseVector<f32>, output: &mut [f64], scratch: &mut Vec<f64>) {
+        kernel.compute_with_scratch(&self.support_vectors, feature, output, scratch)
+    }
+
+    /// Returns all attributes of support vector `i_vector` as written to a model file, with
+    /// missing attributes filled in with `0.0` like libSVM assumes.
+    crate fn dense_attributes(&self, i_vector: usize, num_attributes: usize) -> Vec<Attribute> {
+        let mut attributes = (0 .. num_attributes as u32).map(|index| Attribute { index, value: 0.0 }).collect::<Vec<_>>();
+
+        for (index, value) in self.support_vectors.row(i_vector).iter() {
+            attributes[index as usize].value = f64::from(value);
         }
+
+        attributes
     }
 }
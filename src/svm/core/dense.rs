@@ -1,29 +1,718 @@
 use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
-use std::{convert::TryFrom, marker::PhantomData};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    marker::PhantomData,
+};
+
+#[cfg(feature = "io")]
+use std::io::{BufRead, Write};
 
 use crate::{
     errors::Error,
-    parser::ModelFile,
+    parser::{ModelFile, SupportVector},
     svm::{
         class::Class,
-        core::SVMCore,
-        kernel::{KernelDense, Linear, Poly, Rbf, Sigmoid},
+        core::{SVMCore, Settings},
+        kernel::{padding_mask, squared_distance, squared_distance_f64, KernelDense, KernelInfo, Linear, Poly, Precomputed, Rbf, Sigmoid},
         predict::Predict,
-        problem::{Problem, Solution},
-        DenseSVM, Probabilities, SVMType,
+        problem::{LabelSource, Problem, Solution},
+        DenseSVM, KernelPrecision, PrecisionMode, Probabilities, ProbabilitySettings, SVMType,
     },
-    util::{find_max_index, set_all, sigmoid_predict},
+    util::{compensated_dot, find_max_index, set_all, sigmoid_predict},
     vectors::Triangular,
 };
 
 impl DenseSVM {
     impl_common_svm!(SimdVector<f32s>);
+
+    /// Classifies half-precision features and returns the predicted label.
+    ///
+    /// # Description
+    ///
+    /// Convenience method for pipelines producing `half::f16` features. Like
+    /// [DenseSVM::classify_slice], the features are widened into the given, reused [Problem]
+    /// (using hardware conversion where available), which is then predicted.
+    ///
+    /// Only available with the `half` feature enabled.
+    ///
+    /// # Returns
+    ///
+    /// The predicted label, [Error::DimensionMismatch] if the number of features does not match
+    /// the model, or [Error::NotClassifier] for regression models.
+    #[cfg(feature = "half")]
+    pub fn classify_f16(&self, problem: &mut Problem<SimdVector<f32s>>, features: &[half::f16]) -> Result<i32, Error> {
+        use half::slice::HalfFloatSliceExt;
+
+        self.check_num_features(features.len())?;
+
+        features.convert_to_f32_slice(&mut problem.features().as_slice_mut()[.. features.len()]);
+
+        self.predict_value(problem)?;
+
+        match problem.result {
+            Solution::Label(label) => Ok(label),
+            _ => Err(Error::NotClassifier),
+        }
+    }
+
+    /// Classifies features given as a plain slice (or fixed size array) and returns the predicted label.
+    ///
+    /// # Description
+    ///
+    /// The features are copied into the given, reused [Problem] which is then predicted, so no
+    /// allocation happens per call. The result is identical to setting the features of `problem`
+    /// manually and calling [Predict::predict_value].
+    ///
+    /// # Returns
+    ///
+    /// The predicted label, [Error::DimensionMismatch] if the number of features does not match
+    /// the model, or [Error::NotClassifier] for regression and one-class models.
+    pub fn classify_slice(&self, problem: &mut Problem<SimdVector<f32s>>, features: &[f32]) -> Result<i32, Error> {
+        self.check_num_features(features.len())?;
+
+        problem.features().as_slice_mut()[.. features.len()].copy_from_slice(features);
+
+        self.predict_value(problem)?;
+
+        match problem.result {
+            Solution::Label(label) => Ok(label),
+            _ => Err(Error::NotClassifier),
+        }
+    }
+
+    /// Classifies the given features and returns the predicted label.
+    ///
+    /// # Description
+    ///
+    /// The simplest way to classify a single sample. A fresh [Problem] is allocated on every
+    /// call; when classifying many samples, prefer [DenseSVM::classify_slice] with a reused one.
+    ///
+    /// ```
+    /// #![feature(try_from)]
+    ///
+    /// use ffsvm::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+    ///
+    ///     assert_eq!(svm.predict_label(&[0.23122, 0.0, 0.0, 0.0])?, 42);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The predicted label, [Error::DimensionMismatch] if the number of features does not match
+    /// the model, or [Error::NotClassifier] for regression and one-class models.
+    pub fn predict_label(&self, features: &[f32]) -> Result<i32, Error> { self.classify_slice(&mut Problem::from(self), features) }
+
+    /// Predicts comma separated feature rows and writes one JSON object per prediction.
+    ///
+    /// # Description
+    ///
+    /// Each non-empty line of `input` must contain exactly [SVMCore::attributes] comma separated
+    /// numbers. For each line, a JSON object like `{"label":42,"confidence":0.93}` is written as a
+    /// separate line to `output` (see [SVMCore::predict_with_confidence] for the meaning of the
+    /// confidence). Rows are processed one at a time with a single reused [Problem], so arbitrarily
+    /// large inputs can be streamed. Confidences that are not finite are written as `null`.
+    ///
+    /// Only available with the `io` feature enabled.
+    ///
+    /// # Returns
+    ///
+    /// The number of predictions written, [Error::CsvRow] with the 1-based line number if a row
+    /// has the wrong number of columns or an invalid number, or any other error encountered.
+    #[cfg(feature = "io")]
+    pub fn predict_jsonl<R, W>(&self, input: R, mut output: W) -> Result<usize, Error>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut problem = Problem::from(self);
+        let mut count = 0;
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            self.read_csv_row(&mut problem, i + 1, &line)?;
+
+            let (label, confidence) = self.predict_with_confidence(&mut problem)?;
+
+            // JSON has no representation for `NaN` or infinities.
+            if confidence.is_finite() {
+                writeln!(output, "{{\"label\":{},\"confidence\":{}}}", label, confidence)?;
+            } else {
+                writeln!(output, "{{\"label\":{},\"confidence\":null}}", label)?;
+            }
+
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Predicts comma separated feature rows and writes one prediction per line, like `svm-predict`.
+    ///
+    /// # Description
+    ///
+    /// Each non-empty line of `reader` must contain exactly [SVMCore::attributes] comma separated
+    /// numbers. For each line the predicted label (classification), value (regression) or `1` / `-1`
+    /// (one-class inlier / outlier) is written as a separate line to `writer`. Rows are processed
+    /// one at a time with a single reused [Problem], so arbitrarily large files can be streamed.
+    ///
+    /// Only available with the `io` feature enabled.
+    ///
+    /// # Returns
+    ///
+    /// The number of predictions written, [Error::CsvRow] with the 1-based line number if a row
+    /// has the wrong number of columns or an invalid number, or any other error encountered.
+    #[cfg(feature = "io")]
+    pub fn predict_csv<R, W>(&self, reader: R, mut writer: W) -> Result<usize, Error>
+    where
+        R: std::io::Read,
+        W: Write,
+    {
+        let mut problem = Problem::from(self);
+        let mut count = 0;
+
+        for (i, line) in std::io::BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            self.read_csv_row(&mut problem, i + 1, &line)?;
+            self.predict_value(&mut problem)?;
+
+            match problem.result {
+                Solution::Label(label) => writeln!(writer, "{}", label)?,
+                Solution::Value(value) => writeln!(writer, "{}", value)?,
+                Solution::Inlier(inlier) => writeln!(writer, "{}", if inlier { 1 } else { -1 })?,
+                Solution::None => {
+                    return Err(Error::CsvRow {
+                        line: i + 1,
+                        detail: "No prediction was made for this row".to_string(),
+                    });
+                }
+            }
+
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Parses one row of comma separated numbers into the features of `problem`.
+    ///
+    /// Errors are reported as [Error::CsvRow] with the given 1-based `line` number.
+    #[cfg(feature = "io")]
+    fn read_csv_row(&self, problem: &mut Problem<SimdVector<f32s>>, line: usize, row: &str) -> Result<(), Error> {
+        let features = problem.features().as_slice_mut();
+        let mut num_values = 0;
+
+        for value in row.split(',') {
+            if num_values < self.num_attributes {
+                features[num_values] = value.trim().parse::<f32>().map_err(|_| Error::CsvRow {
+                    line,
+                    detail: format!("Invalid number `{}`", value.trim()),
+                })?;
+            }
+
+            num_values += 1;
+        }
+
+        if num_values != self.num_attributes {
+            return Err(Error::CsvRow {
+                line,
+                detail: format!("Expected {} columns, found {}", self.num_attributes, num_values),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `len` features match the model.
+    fn check_num_features(&self, len: usize) -> Result<(), Error> {
+        if len != self.num_attributes {
+            return Err(Error::DimensionMismatch {
+                expected: self.num_attributes,
+                actual: len,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that there is one label per input and that all inputs match the model.
+    fn check_labeled_inputs(&self, inputs: &[Vec<f32>], labels: &[i32]) -> Result<(), Error> {
+        if inputs.len() != labels.len() {
+            return Err(Error::DimensionMismatch {
+                expected: inputs.len(),
+                actual: labels.len(),
+            });
+        }
+
+        inputs.iter().try_for_each(|row| self.check_num_features(row.len()))
+    }
+
+    /// Computes the decision threshold of a binary classifier that best achieves a target false positive rate.
+    ///
+    /// # Description
+    ///
+    /// The class with index `0` is considered the positive class, all `inputs` whose label
+    /// differs from it are negatives. For every negative the decision value is computed, and
+    /// all possible thresholds `t` are swept to find the one where the fraction of negatives
+    /// with a decision value above `t` is closest to `target_fpr`.
+    ///
+    /// To make the model predict with that threshold, shift its bias accordingly:
+    ///
+    /// ```ignore
+    /// let t = svm.calibrate_threshold_for_fpr(&inputs, &labels, 0.01)?;
+    /// svm.set_rho(0, 1, rho + t)?;
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The threshold, `0.0` if there are no negatives, [Error::NotClassifier] unless this is a
+    /// binary classifier, [Error::DimensionMismatch] if `labels` does not match `inputs` or
+    /// a row does not match the model, or [Error::UnknownLabel] for the first label that is
+    /// not one of the model's labels.
+    pub fn calibrate_threshold_for_fpr(&self, inputs: &[Vec<f32>], labels: &[i32], target_fpr: f64) -> Result<f64, Error> {
+        if !self.svm_type.is_classifier() || self.classes.len() != 2 {
+            return Err(Error::NotClassifier);
+        }
+
+        self.check_labeled_inputs(inputs, labels)?;
+
+        // Otherwise a typo in `labels` would silently count as a negative.
+        if let Some(label) = labels.iter().find(|label| self.class_index_for_label(**label).is_none()) {
+            return Err(Error::UnknownLabel { label: *label });
+        }
+
+        let positive = self.classes[0].label;
+        let mut problem = Problem::from(self);
+        let mut negatives = Vec::new();
+
+        for (row, _) in inputs.iter().zip(labels).filter(|(_, label)| **label != positive) {
+            problem.features().as_slice_mut()[.. row.len()].copy_from_slice(row);
+            self.predict_value(&mut problem)?;
+
+            negatives.push(problem.decision_values[(0, 1)]);
+        }
+
+        if negatives.is_empty() {
+            return Ok(0.0);
+        }
+
+        // Sorted descending, the number of values strictly above `negatives[k]` is the index of
+        // its first occurrence.
+        negatives.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        let num_negatives = negatives.len() as f64;
+        let mut best_threshold = negatives[negatives.len() - 1] - 1.0;
+        let mut best_error = (1.0 - target_fpr).abs();
+        let mut first_occurrence = 0;
+
+        for (k, threshold) in negatives.iter().enumerate() {
+            if *threshold != negatives[first_occurrence] {
+                first_occurrence = k;
+            }
+
+            let error = (first_occurrence as f64 / num_negatives - target_fpr).abs();
+
+            if error < best_error {
+                best_error = error;
+                best_threshold = *threshold;
+            }
+        }
+
+        Ok(best_threshold)
+    }
+
+    /// Computes the mean winning margin of all `inputs`, grouped by their true label.
+    ///
+    /// # Description
+    ///
+    /// Every input is predicted, and its margin is the smallest decision value (see
+    /// [Problem::decision_value]) between the winning class and any other class, i.e., how far
+    /// the prediction is from flipping. The margins are then averaged per true label, so classes
+    /// the model is least confident about have the lowest means. Wrong predictions contribute
+    /// the margin of the class that won.
+    ///
+    /// # Returns
+    ///
+    /// The mean margin per label found in `labels`, [Error::NotClassifier] for regression and
+    /// one-class models, or [Error::DimensionMismatch] if `labels` does not match `inputs` or a
+    /// row does not match the model.
+    pub fn per_class_mean_margin(&self, inputs: &[Vec<f32>], labels: &[i32]) -> Result<HashMap<i32, f64>, Error> {
+        if !self.svm_type.is_classifier() {
+            return Err(Error::NotClassifier);
+        }
+
+        self.check_labeled_inputs(inputs, labels)?;
+
+        let mut problem = Problem::from(self);
+        let mut sums = HashMap::<i32, (f64, usize)>::new();
+
+        for (row, label) in inputs.iter().zip(labels) {
+            problem.features().as_slice_mut()[.. row.len()].copy_from_slice(row);
+            self.predict_value(&mut problem)?;
+
+            let winner = find_max_index(&problem.vote);
+            let margin = (0 .. self.classes.len())
+                .filter_map(|other| problem.decision_value(winner, other))
+                .fold(std::f64::INFINITY, f64::min);
+
+            let sum = sums.entry(*label).or_insert((0.0, 0));
+            sum.0 += margin;
+            sum.1 += 1;
+        }
+
+        Ok(sums.into_iter().map(|(label, (sum, count))| (label, sum / count as f64)).collect())
+    }
+
+    /// Returns the SIMD padding lanes following the attributes of a support vector.
+    ///
+    /// Kernels process whole SIMD lanes. Padding is zero after loading, and the built-in kernels
+    /// do not let it affect their results either way. Only available with the `testing` feature.
+    ///
+    /// # Panics
+    ///
+    /// If `class` or `sv` are out of range.
+    #[cfg(feature = "testing")]
+    pub fn support_vector_padding(&self, class: usize, sv: usize) -> Vec<f32> {
+        self.classes[class]
+            .support_vectors
+            .row(sv)
+            .iter()
+            .flat_map(|x| (0 .. f32s::lanes()).map(move |lane| x.extract(lane)))
+            .skip(self.num_attributes)
+            .collect()
+    }
+
+    /// Overwrites the SIMD padding lanes of a support vector with `value`, e.g., to check they
+    /// are handled correctly. Overwrites the padding of the `f64` support vectors used for
+    /// [KernelPrecision::Double] as well. Only available with the `testing` feature.
+    ///
+    /// # Panics
+    ///
+    /// If `class` or `sv` are out of range.
+    #[cfg(feature = "testing")]
+    pub fn poison_support_vector_padding(&mut self, class: usize, sv: usize, value: f32) {
+        let num_attributes = self.num_attributes;
+
+        let class = &mut self.classes[class];
+
+        for (i, x) in class.support_vectors.row_mut(sv).iter_mut().enumerate() {
+            for lane in 0 .. f32s::lanes() {
+                if i * f32s::lanes() + lane >= num_attributes {
+                    *x = x.replace(lane, value);
+                }
+            }
+        }
+
+        if let Some(vectors_f64) = &mut class.support_vectors_f64 {
+            for (i, x) in vectors_f64.row_mut(sv).iter_mut().enumerate() {
+                for lane in 0 .. f64s::lanes() {
+                    if i * f64s::lanes() + lane >= num_attributes {
+                        *x = x.replace(lane, f64::from(value));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finds where the decision function of a class pair changes its sign along a ray.
+    ///
+    /// # Description
+    ///
+    /// Points on the ray are `origin + t * direction`. The decision value of the class pair
+    /// `(i, j)` is evaluated at both ends of `t_range` and, if the signs differ, the crossing
+    /// is located by bisection until the remaining interval is smaller than `tol`.
+    ///
+    /// # Returns
+    ///
+    /// The parameter `t` of the crossing, `None` if the decision value has the same sign at both
+    /// ends of `t_range`, [Error::DimensionMismatch] if `origin` or `direction` don't match the
+    /// model, or [Error::InvalidClassPair] unless `i < j` are valid class indices.
+    pub fn boundary_crossing(&self, origin: &[f32], direction: &[f32], class_pair: (usize, usize), t_range: (f64, f64), tol: f64) -> Result<Option<f64>, Error> {
+        let (i, j) = class_pair;
+
+        if i >= j || j >= self.classes.len() {
+            return Err(Error::InvalidClassPair { i, j });
+        }
+
+        self.check_num_features(origin.len())?;
+        self.check_num_features(direction.len())?;
+
+        let mut problem = Problem::from(self);
+
+        let mut decision_value_at = |t: f64| -> Result<f64, Error> {
+            for (feature, (o, d)) in problem.features().as_slice_mut().iter_mut().zip(origin.iter().zip(direction)) {
+                *feature = (f64::from(*o) + t * f64::from(*d)) as f32;
+            }
+
+            self.predict_value(&mut problem)?;
+
+            Ok(problem.decision_values[(i, j)])
+        };
+
+        let (mut low, mut high) = t_range;
+        let low_positive = decision_value_at(low)? > 0.0;
+
+        if low_positive == (decision_value_at(high)? > 0.0) {
+            return Ok(None);
+        }
+
+        while (high - low).abs() > tol {
+            let middle = 0.5 * (low + high);
+
+            if (decision_value_at(middle)? > 0.0) == low_positive {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Ok(Some(0.5 * (low + high)))
+    }
+
+    /// Computes how entangled the support vectors of two classes are.
+    ///
+    /// # Description
+    ///
+    /// Returns the mean kernel value over all pairs of support vectors of class index `i`
+    /// and class index `j`, using the model's kernel. Higher values (e.g., close to `1.0` for
+    /// RBF kernels) indicate the two classes are hard to separate and will likely be confused.
+    ///
+    /// # Returns
+    ///
+    /// The mean kernel value, or `None` if either index is not a valid class index.
+    pub fn class_overlap(&self, i: usize, j: usize) -> Option<f64> {
+        let class_i = self.classes.get(i)?;
+        let class_j = self.classes.get(j)?;
+
+        let mut feature = SimdVector::<f32s>::with(0.0, self.num_attributes);
+        let mut kernel_values = vec![0.0; class_j.num_support_vectors];
+        let mut sum = 0.0;
+
+        let support_vectors = class_i.support_vectors.flat();
+
+        for i_vector in 0 .. class_i.num_support_vectors {
+            let flat = feature.flat_mut();
+
+            for i_attribute in 0 .. self.num_attributes {
+                flat[i_attribute] = support_vectors[(i_vector, i_attribute)];
+            }
+
+            self.kernel.compute(&class_j.support_vectors, &feature, &mut kernel_values);
+            sum += kernel_values.iter().sum::<f64>();
+        }
+
+        Some(sum / (class_i.num_support_vectors * class_j.num_support_vectors).max(1) as f64)
+    }
+
+    /// Computes the decision values of a [Problem] for several RBF gammas in one pass.
+    ///
+    /// # Description
+    ///
+    /// The RBF kernel is `exp(-gamma * d²)` and the squared distances `d²` between the features
+    /// and all support vectors do not depend on `gamma`. They are computed once, and only the
+    /// cheap exponentiation and decision steps are repeated per gamma. This is useful for
+    /// analyzing how sensitive a prediction is to `gamma`.
+    ///
+    /// The problem's features must be set, it does not need to be predicted first and is not
+    /// modified. Kernel values are computed with the SVM's [KernelPrecision], and decision values
+    /// match what [Predict::predict_value] computes for a model with that `gamma`, up to rounding.
+    ///
+    /// # Returns
+    ///
+    /// One triangle of decision values per gamma (see [Problem::decision_value] for their sign),
+    /// [Error::NotRbf] for other kernels, [Error::NotClassifier] for regression and one-class
+    /// models, or [Error::DimensionMismatch] if the problem was not created for this model.
+    pub fn decision_values_for_gammas(&self, problem: &Problem<SimdVector<f32s>>, gammas: &[f64]) -> Result<Vec<Triangular<f64>>, Error> {
+        if !self.svm_type.is_classifier() {
+            return Err(Error::NotClassifier);
+        }
+
+        match self.kernel_info {
+            KernelInfo::Rbf { .. } => {}
+            _ => return Err(Error::NotRbf),
+        }
+
+        self.check_dimensions(problem)?;
+
+        let feature: &[f32s] = &problem.features.data;
+        let mask = padding_mask(self.num_attributes);
+
+        // Squared distances are stored as `f64` either way, for `Single` they hold an `f32` exactly.
+        let distances = self
+            .classes
+            .iter()
+            .map(|class| {
+                (0 .. class.num_support_vectors)
+                    .map(|i| match (self.kernel_precision, &class.support_vectors_f64) {
+                        (KernelPrecision::Double, Some(vectors_f64)) => squared_distance_f64(vectors_f64.row_as_flat(i), problem.features.data.flat()),
+                        _ => f64::from(squared_distance(class.support_vectors.row(i), feature, mask)),
+                    }).collect::<Vec<_>>()
+            }).collect::<Vec<_>>();
+
+        let mut result = Vec::with_capacity(gammas.len());
+
+        for gamma in gammas {
+            let kernel_values = distances
+                .iter()
+                .map(|class| {
+                    class
+                        .iter()
+                        .map(|d| match self.kernel_precision {
+                            // Same precision as the kernel itself.
+                            KernelPrecision::Single => f64::from((-(*gamma as f32) * *d as f32).exp()),
+                            KernelPrecision::Double => (-gamma * d).exp(),
+                        }).collect::<Vec<_>>()
+                }).collect::<Vec<_>>();
+
+            let mut decision_values = Triangular::with_dimension(self.classes.len(), 0.0);
+
+            for i in 0 .. self.classes.len() {
+                for j in (i + 1) .. self.classes.len() {
+                    let dot = |class: usize, row: usize| -> f64 {
+                        let coefficients = self.classes[class].coefficients.row_as_flat(row);
+                        coefficients.iter().zip(&kernel_values[class]).map(|(c, k)| c * k).sum::<f64>()
+                    };
+
+                    let sum0 = dot(i, j - 1);
+                    let sum1 = dot(j, i);
+
+                    decision_values[(i, j)] = sum0 * self.classes[i].correction + sum1 * self.classes[j].correction - self.rho[(i, j)];
+                }
+            }
+
+            result.push(decision_values);
+        }
+
+        Ok(result)
+    }
 }
 
 impl Predict<SimdVector<f32s>, SimdVector<f64s>> for DenseSVM {
     impl_common_predict!(SimdVector<f32s>);
 }
 
+impl DenseSVM {
+    /// Writes this SVM and all its settings in the compact binary form [DenseSVM::from_bincode] loads.
+    ///
+    /// Only available with the `binary` feature enabled.
+    ///
+    /// # Returns
+    ///
+    /// The serialized SVM, or [Error::CustomKernel] for custom kernels.
+    #[cfg(feature = "binary")]
+    pub fn to_bincode(&self) -> Result<Vec<u8>, Error> {
+        let model = self.to_model_file()?;
+
+        bincode::serialize(&(model, self.settings())).map_err(|e| Error::ParsingError(format!("{}", e)))
+    }
+
+    /// Loads an SVM written by [DenseSVM::to_bincode], e.g., embedded into the executable via
+    /// `include_bytes!("model.bin")`.
+    ///
+    /// # Description
+    ///
+    /// Unlike [SVMCore::from_static_bytes] no libSVM text has to be parsed, and all settings
+    /// stored with the SVM are restored as well (see the `Serialize` implementation). Nothing is
+    /// borrowed from `bytes`: the model is decoded into owned data first, and its support vectors
+    /// are then copied into SIMD-aligned storage.
+    ///
+    /// Only available with the `binary` feature enabled.
+    ///
+    /// # Returns
+    ///
+    /// The SVM, [Error::ParsingError] if the bytes are not a serialized SVM, or any error
+    /// [TryFrom] would return for the contained model.
+    #[cfg(feature = "binary")]
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, Error> {
+        let (model, settings): (ModelFile<'static>, Settings) = bincode::deserialize(bytes).map_err(|e| Error::ParsingError(format!("{}", e)))?;
+
+        let mut svm = DenseSVM::try_from(&model)?;
+        svm.apply_settings(settings)?;
+
+        Ok(svm)
+    }
+}
+
+/// Serializes the SVM as its [ModelFile] together with all settings not part of libSVM's format,
+/// i.e., [PrecisionMode], [KernelPrecision], [ProbabilitySettings], probability warm start,
+/// parallel kernels, linear fast mode, class frequency corrections and metadata. Loading it again
+/// skips parsing the libSVM text format, and the loaded SVM predicts exactly like this one.
+///
+/// SVMs with a custom kernel (see [DenseSVM::from_parts]) can't be serialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for DenseSVM {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let model = self.to_model_file().map_err(|e| serde::ser::Error::custom(format!("{:?}", e)))?;
+
+        serde::Serialize::serialize(&(model, self.settings()), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DenseSVM {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (model, settings): (ModelFile<'static>, Settings) = serde::Deserialize::deserialize(deserializer)?;
+
+        let mut svm = DenseSVM::try_from(&model).map_err(|e| serde::de::Error::custom(format!("{:?}", e)))?;
+        svm.apply_settings(settings).map_err(|e| serde::de::Error::custom(format!("{:?}", e)))?;
+
+        Ok(svm)
+    }
+}
+
+/// Writes the SVM in libSVM's model format, so `svm.to_string()` can be loaded again. Fails
+/// for custom kernels, see [DenseSVM::to_model_string].
+impl fmt::Display for DenseSVM {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_header(f)?;
+
+        // Classifiers have one coefficient per other class, regression and one-class models one.
+        let num_coefficients = (self.classes.len() - 1).max(1);
+
+        for class in &self.classes {
+            let coefficients = class.coefficients.flat();
+
+            for i_vector in 0 .. class.num_support_vectors {
+                for i_coefficient in 0 .. num_coefficients {
+                    write!(f, "{} ", coefficients[(i_coefficient, i_vector)])?;
+                }
+
+                // The `f64` values of the model file, so nothing is lost for `KernelPrecision::Double`.
+                for attribute in class.dense_attributes(i_vector, self.num_attributes) {
+                    write!(f, "{}:{} ", attribute.index, attribute.value)?;
+                }
+
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a, 'b> TryFrom<&'a str> for DenseSVM {
     type Error = Error;
 
@@ -36,9 +725,105 @@ impl<'a, 'b> TryFrom<&'a str> for DenseSVM {
 impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for DenseSVM {
     type Error = Error;
 
-    fn try_from(raw_model: &'a ModelFile<'_>) -> Result<DenseSVM, Error> {
-        let (mut svm, nr_sv) = prepare_svm!(raw_model, dyn KernelDense, SimdMatrix<f32s, RowOptimized>);
+    fn try_from(raw_model: &'a ModelFile<'_>) -> Result<DenseSVM, Error> { DenseSVM::from_model_file(raw_model, false) }
+}
+
+impl DenseSVM {
+    /// Loads a libSVM model, storing each attribute at its index instead of its position.
+    ///
+    /// # Description
+    ///
+    /// [TryFrom] places the first attribute of every support vector at feature `0`, the second at
+    /// feature `1`, and so on, whatever their indices are. For models written with 1-based indices
+    /// (the default of libSVM's `svm-train`) index `1` therefore becomes feature `0`.
+    ///
+    /// This method instead places attribute `i` at feature `i` and sizes the model by the highest
+    /// index found in any support vector. For 1-based models feature `0` is then unused and always
+    /// multiplied by zero. Indices must still be consecutive within each support vector.
+    ///
+    /// # Returns
+    ///
+    /// The SVM, or any error [TryFrom] would return for the model.
+    pub fn try_from_indexed(model: &str) -> Result<DenseSVM, Error> {
+        let raw_model = ModelFile::try_from(model)?;
+        DenseSVM::from_model_file(&raw_model, true)
+    }
+
+    /// Creates the SVM, storing attributes at their index if `by_index` is set, or at their position otherwise.
+    crate fn from_model_file(raw_model: &ModelFile<'_>, by_index: bool) -> Result<DenseSVM, Error> {
+        let (mut svm, nr_sv) = prepare_svm!(raw_model, by_index, dyn KernelDense, SimdMatrix<f32s, RowOptimized>);
+
+        svm.load_support_vectors(raw_model, &nr_sv, by_index)?;
+
+        Ok(svm)
+    }
+
+    /// Creates a SVM from a parsed model, but with a kernel of your own.
+    ///
+    /// # Description
+    ///
+    /// All numeric data (support vectors, coefficients, `rho`, probabilities, ...) is taken from
+    /// `raw_model`, while its `kernel_type` and kernel parameters are ignored. Instead, `kernel`
+    /// is used for all predictions, see [KernelDense] on how to implement one. The model must
+    /// have been trained with the same kernel for predictions to make sense.
+    ///
+    /// Since custom kernels can't be expressed in libSVM's format, the resulting SVM reports
+    /// [KernelInfo::Custom], and [DenseSVM::to_model_string] returns [Error::CustomKernel].
+    ///
+    /// # Returns
+    ///
+    /// The SVM, or any error [TryFrom] would return for the model's numeric data.
+    pub fn from_parts(raw_model: &ModelFile<'_>, kernel: Box<dyn KernelDense>) -> Result<DenseSVM, Error> {
+        let (mut svm, nr_sv) = prepare_svm!(raw_model, false, dyn KernelDense, SimdMatrix<f32s, RowOptimized>, (kernel, KernelInfo::Custom));
 
+        svm.load_support_vectors(raw_model, &nr_sv, false)?;
+
+        Ok(svm)
+    }
+
+    /// Returns a copy of this SVM that uses `kernel` instead of its own.
+    ///
+    /// # Description
+    ///
+    /// Support vectors, coefficients, `rho`, probabilities and all other settings are copied,
+    /// only the kernel is replaced. This is meant for experiments, e.g., to see how a decision
+    /// changes under another kernel.
+    ///
+    /// **Note**: This changes prediction semantics. The coefficients were trained for the original
+    /// kernel, so predictions with any other kernel are not what the model learned. The copy
+    /// reports [KernelInfo::Custom], and linear fast mode (see [SVMCore::enable_linear_fast]) is
+    /// not carried over.
+    pub fn with_replaced_kernel(&self, kernel: Box<dyn KernelDense>) -> DenseSVM {
+        let mut classes = self.classes.clone();
+
+        for class in &mut classes {
+            class.linear_weights = None;
+        }
+
+        SVMCore {
+            num_total_sv: self.num_total_sv,
+            num_attributes: self.num_attributes,
+            rho: self.rho.clone(),
+            probabilities: self.probabilities.clone(),
+            svm_type: self.svm_type,
+            precision: self.precision,
+            kernel_precision: self.kernel_precision,
+            probability_warm_start: self.probability_warm_start,
+            probability_settings: self.probability_settings,
+            parallel_kernels: self.parallel_kernels,
+            kernel,
+            kernel_info: KernelInfo::Custom,
+            classes,
+            metadata: self.metadata.clone(),
+            phantom_v32: PhantomData,
+            phantom_v64: PhantomData,
+        }
+    }
+
+    /// Copies support vectors and coefficients of `raw_model` into the (already allocated) classes,
+    /// storing attributes at their index if `by_index` is set, or at their position otherwise.
+    fn load_support_vectors(&mut self, raw_model: &ModelFile<'_>, nr_sv: &[u32], by_index: bool) -> Result<(), Error> {
+        let svm = self;
         let vectors = &raw_model.vectors;
 
         // Things down here are a bit ugly as the file format is a bit ugly ...
@@ -67,8 +852,14 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for DenseSVM {
                         }
                     };
 
-                    let mut support_vectors = svm.classes[i].support_vectors.flat_mut();
-                    support_vectors[(i_vector, i_attribute)] = attribute.value;
+                    let column = if by_index { attribute.index as usize } else { i_attribute };
+
+                    let class = &mut svm.classes[i];
+                    class.support_vectors.flat_mut()[(i_vector, column)] = attribute.value as f32;
+
+                    if let Some(vectors_f64) = &mut class.support_vectors_f64 {
+                        vectors_f64.flat_mut()[(i_vector, column)] = attribute.value;
+                    }
 
                     last_attribute = Some(attribute.index);
                 }
@@ -76,7 +867,7 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for DenseSVM {
                 // Set coefficients
                 for (i_coefficient, coefficient) in vector.coefs.iter().enumerate() {
                     let mut coefficients = svm.classes[i].coefficients.flat_mut();
-                    coefficients[(i_coefficient, i_vector)] = f64::from(*coefficient);
+                    coefficients[(i_coefficient, i_vector)] = *coefficient;
                 }
             }
 
@@ -84,14 +875,14 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for DenseSVM {
             start_offset = stop_offset;
         }
 
-        // Return what we have
-        Result::Ok(svm)
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use std::convert::TryFrom;
 
     #[test]
@@ -104,4 +895,2061 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn labels() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        assert_eq!(svm.labels().collect::<Vec<_>>(), vec![21, 42]);
+
+        for (i, label) in svm.labels().enumerate() {
+            assert_eq!(svm.class_label_for_index(i), Some(label));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn class_overlap() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+
+        // Classes 0 and 1 have support vectors close to each other, classes 0 and 7 don't.
+        let close = svm.class_overlap(0, 1).unwrap();
+        let far = svm.class_overlap(0, 7).unwrap();
+
+        assert!(close > 0.9);
+        assert!(far < 0.5);
+        assert!((far - svm.class_overlap(7, 0).unwrap()).abs() < 1e-6);
+        assert_eq!(svm.class_overlap(0, 8), None);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "half")]
+    fn classify_f16() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let values = [1.2974607, 1.0227317, 1.2545854, 0.0];
+
+        let mut problem = Problem::from(&svm);
+        problem.features().as_slice_mut().clone_from_slice(&values);
+        svm.predict_value(&mut problem)?;
+
+        let features = values.iter().map(|x| half::f16::from_f32(*x)).collect::<Vec<_>>();
+        let mut reused = Problem::from(&svm);
+
+        assert_eq!(problem.solution(), Solution::Label(svm.classify_f16(&mut reused, &features)?));
+        assert_eq!(reused.solution(), problem.solution());
+        assert!(svm.classify_f16(&mut reused, &features[.. 2]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn probability_settings() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3;
+        }
+
+        svm.predict_probability(&mut problem)?;
+        let default_iterations = problem.probability_iterations();
+
+        // A huge epsilon is met right away ...
+        svm.set_probability_settings(ProbabilitySettings { max_iterations: 100, epsilon: 1e6 });
+        svm.predict_probability(&mut problem)?;
+        assert_eq!(problem.probability_iterations(), 1);
+
+        // ... while a tiny one needs more iterations than the default ...
+        svm.set_probability_settings(ProbabilitySettings { max_iterations: 10_000, epsilon: 1e-9 });
+        svm.predict_probability(&mut problem)?;
+        assert!(problem.probability_iterations() >= default_iterations);
+
+        // ... and one that can't be met gives up after the initial round plus 20 iterations.
+        svm.set_probability_settings(ProbabilitySettings { max_iterations: 20, epsilon: 0.0 });
+
+        match svm.predict_probability(&mut problem) {
+            Err(Error::IterationsExceeded { iterations: 20, .. }) => {}
+            other => panic!("Expected Error::IterationsExceeded, got {:?}", other),
+        }
+
+        assert_eq!(problem.probability_iterations(), 21);
+
+        Ok(())
+    }
+
+    #[test]
+    fn iterations_exceeded() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        {
+            let mut pairwise = problem.pairwise.flat_mut();
+
+            for i in 0 .. svm.classes() {
+                for j in i + 1 .. svm.classes() {
+                    pairwise[(i, j)] = 0.9;
+                    pairwise[(j, i)] = 0.1;
+                }
+            }
+        }
+
+        // With a limit of `0` iterations only the convergence check of the uniform starting
+        // probabilities runs, which fails for these strongly ordered pairwise probabilities. The
+        // error reports that limit.
+        match svm.compute_multiclass_probabilities(&mut problem, 0) {
+            Err(Error::IterationsExceeded { iterations, max_error, eps }) => {
+                assert_eq!(iterations, 0);
+                assert!(max_error > eps);
+                assert!(eps > 0.0);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn decision_score_matches_label() -> Result<(), Error> {
+        let mut rng = StdRng::seed_from_u64(527);
+        let svm = DenseSVM::try_from(&ModelFile::random_dense_with_rng(&mut rng, "c_svc", "rbf", 64, 8))?;
+        let mut problem = Problem::from(&svm);
+
+        for _ in 0 .. 100 {
+            for i in 0 .. svm.attributes() {
+                problem.features()[i] = rng.gen::<f32>();
+            }
+
+            let score = svm.decision_score(&mut problem)?;
+            let favored = if score > 0.0 { 0 } else { 1 };
+
+            assert_eq!(problem.solution(), Solution::Label(svm.class_label_for_index(favored).unwrap()));
+        }
+
+        let multiclass = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+
+        match multiclass.decision_score(&mut Problem::from(&multiclass)) {
+            Err(Error::NotClassifier) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_static_bytes() -> Result<(), Error> {
+        static MODEL: &[u8] = include_bytes!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+
+        let svm = DenseSVM::from_static_bytes(MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.0001;
+        }
+
+        svm.predict_value(&mut problem)?;
+
+        assert_eq!(problem.solution(), Solution::Label(0));
+
+        match DenseSVM::from_static_bytes(&[0xff, 0xfe]) {
+            Err(Error::ParsingError(_)) => {}
+            _ => panic!("Expected Error::ParsingError"),
+        }
+
+        Ok(())
+    }
+
+    /// The SVM stored in `tests/data_dense/sample.bincode`.
+    #[cfg(feature = "binary")]
+    fn bincode_fixture_svm() -> Result<DenseSVM, Error> {
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        svm.set_precision_mode(PrecisionMode::Precise);
+        svm.set_metadata("version", "1.2.0");
+
+        Ok(svm)
+    }
+
+    /// Rewrites `tests/data_dense/sample.bincode` after an intended format change, run with
+    /// `cargo test --features binary -- --ignored write_bincode_fixture`.
+    #[cfg(feature = "binary")]
+    #[test]
+    #[ignore]
+    fn write_bincode_fixture() -> Result<(), Error> {
+        let bytes = bincode_fixture_svm()?.to_bincode()?;
+
+        std::fs::write(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data_dense/sample.bincode"), bytes)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn from_bincode() -> Result<(), Error> {
+        // Written by `write_bincode_fixture`, so this also catches unintended format changes.
+        static MODEL: &[u8] = include_bytes!("../../../tests/data_dense/sample.bincode");
+
+        let svm = DenseSVM::from_bincode(MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        assert_eq!(svm.to_string(), DenseSVM::try_from(SAMPLE_MODEL)?.to_string());
+        assert_eq!(svm.precision, PrecisionMode::Precise);
+        assert_eq!(svm.get_metadata("version"), Some("1.2.0"));
+        assert_eq!(svm.to_bincode()?, MODEL);
+        assert_eq!(bincode_fixture_svm()?.to_bincode()?, MODEL);
+
+        problem.features().as_slice_mut().clone_from_slice(&[0.0001, 0.0001, 0.0001, 0.0001]);
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(21));
+
+        problem.features().as_slice_mut().clone_from_slice(&[1.2974607, 1.0227317, 1.2545854, 0.0]);
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(42));
+
+        match DenseSVM::from_bincode(&MODEL[.. 20]) {
+            Err(Error::ParsingError(_)) => {}
+            _ => panic!("Expected Error::ParsingError"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn vote_count_and_margin() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.0001;
+        }
+
+        svm.predict_value(&mut problem)?;
+
+        let mut votes = (0 .. svm.classes()).map(|i| problem.vote_count(i)).collect::<Vec<_>>();
+
+        assert_eq!(votes.iter().sum::<u32>(), 28);
+
+        votes.sort();
+        assert_eq!(problem.winning_margin(), votes[7] - votes[6]);
+
+        // Two classes tie, the lower class index wins.
+        problem.vote = vec![3, 5, 5, 1];
+        assert_eq!(problem.winning_margin(), 0);
+        assert_eq!(crate::util::find_max_index(&problem.vote), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_tie() -> Result<(), Error> {
+        // With all features `0` every decision value is `-rho`, so class 0 beats 1, 2 beats 0 and
+        // 1 beats 2. Each class gets one vote, and the lowest class index wins the tie.
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 3\ntotal_sv 3\nrho -1 1 -1\nlabel 1 2 3\nprobA -2 -2 -2\nprobB 0 0 0\nnr_sv 1 1 1\nSV\n0.5 0.5 0:1 1:1\n-0.5 0.5 0:1 1:-1\n-0.5 -0.5 0:-1 1:1\n";
+        let svm = DenseSVM::try_from(model)?;
+        let mut problem = Problem::from(&svm);
+
+        // Nothing was classified yet.
+        assert!(!problem.is_tie());
+        assert_eq!(problem.label_source(), None);
+
+        svm.predict_value(&mut problem)?;
+
+        assert_eq!((0 .. 3).map(|i| problem.vote_count(i)).collect::<Vec<_>>(), vec![1, 1, 1]);
+        assert_eq!(problem.solution(), Solution::Label(1));
+        assert_eq!(problem.label_source(), Some(LabelSource::Votes));
+        assert!(problem.is_tie());
+
+        // Probability predictions pick their label by probability, even though the votes still tie.
+        svm.predict_probability(&mut problem)?;
+
+        assert_eq!((0 .. 3).map(|i| problem.vote_count(i)).collect::<Vec<_>>(), vec![1, 1, 1]);
+        assert_eq!(problem.label_source(), Some(LabelSource::Probabilities));
+        assert!(!problem.is_tie());
+
+        // A clear winner is no tie.
+        let svm = DenseSVM::try_from(model.replace("rho -1 1 -1", "rho -1 -1 -1").as_str())?;
+
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(1));
+        assert!(!problem.is_tie());
+
+        problem.reset();
+        assert!(!problem.is_tie());
+        assert_eq!(problem.label_source(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decision_values_for_gammas() -> Result<(), Error> {
+        let raw_model = ModelFile::random_dense_multiclass("c_svc", "rbf", 4, 8, 6);
+        let gammas = [0.1, 0.5, 2.0];
+
+        for kernel_precision in &[KernelPrecision::Single, KernelPrecision::Double] {
+            let mut svm = DenseSVM::try_from(&raw_model)?;
+            svm.set_kernel_precision(*kernel_precision);
+
+            let mut problem = Problem::from(&svm);
+
+            for i in 0 .. svm.attributes() {
+                problem.features()[i] = 0.25 + i as f32 * 0.1;
+            }
+
+            let sweep = svm.decision_values_for_gammas(&problem, &gammas)?;
+
+            assert_eq!(sweep.len(), gammas.len());
+
+            for (gamma, decision_values) in gammas.iter().zip(&sweep) {
+                let mut raw_model = raw_model.clone();
+                raw_model.header.gamma = Some(*gamma);
+
+                let mut fresh = DenseSVM::try_from(&raw_model)?;
+                fresh.set_kernel_precision(*kernel_precision);
+                fresh.predict_value(&mut problem)?;
+
+                for (expected, actual) in problem.decision_values.data.iter().zip(&decision_values.data) {
+                    assert!((expected - actual).abs() < 1e-6 * expected.abs().max(1.0));
+                }
+            }
+        }
+
+        let linear = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        match linear.decision_values_for_gammas(&Problem::from(&linear), &gammas) {
+            Err(Error::NotRbf) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_kernel() -> Result<(), Error> {
+        use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+
+        /// Linear kernel, but scaled so it's distinguishable from the built-in one.
+        struct Doubled;
+
+        impl KernelDense for Doubled {
+            fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+                for (i, sv) in vectors.row_iter().enumerate() {
+                    let sum = sv.iter().zip(feature.iter()).fold(f32s::splat(0.0), |acc, (a, b)| acc + *a * *b).sum();
+                    output[i] = 2.0 * f64::from(sum);
+                }
+            }
+        }
+
+        let raw_model = ModelFile::try_from(SAMPLE_MODEL)?;
+        let linear = DenseSVM::try_from(&raw_model)?;
+        let custom = DenseSVM::from_parts(&raw_model, Box::new(Doubled))?;
+
+        let mut problem = Problem::from(&linear);
+        let mut problem_custom = Problem::from(&custom);
+
+        for i in 0 .. linear.attributes() {
+            problem.features()[i] = 0.3 * i as f32;
+            problem_custom.features()[i] = 0.3 * i as f32;
+        }
+
+        linear.predict_value(&mut problem)?;
+        custom.predict_value(&mut problem_custom)?;
+
+        // The decision value is `sum - rho`, so doubling the kernel doubles `sum`.
+        let rho = linear.rho[(0, 1)];
+        let expected = 2.0 * (problem.decision_values[(0, 1)] + rho) - rho;
+
+        assert!((problem_custom.decision_values[(0, 1)] - expected).abs() < 1e-5);
+        assert_eq!(custom.kernel_info(), KernelInfo::Custom);
+
+        match custom.to_model_string() {
+            Err(Error::CustomKernel) => {}
+            other => panic!("Expected Error::CustomKernel, got {:?}", other),
+        }
+
+        assert!(std::fmt::write(&mut String::new(), format_args!("{}", custom)).is_err());
+        assert_eq!(linear.to_model_string()?, linear.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn per_class_mean_margin() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        // Along the first attribute the decision boundary is at about `0.2`. Label 21 lies far
+        // on its side, label 42 scatters around the boundary.
+        let mut inputs = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0 .. 100 {
+            let noise = (i as f32).sin();
+
+            inputs.push(vec![-1.0 + 0.05 * noise, 0.0, 0.0, 0.0]);
+            labels.push(21);
+
+            inputs.push(vec![0.3 + 0.5 * noise, 0.0, 0.0, 0.0]);
+            labels.push(42);
+        }
+
+        let margins = svm.per_class_mean_margin(&inputs, &labels)?;
+
+        assert_eq!(margins.len(), 2);
+        assert!(margins[&21] > 1.0);
+        assert!(margins[&42] < margins[&21]);
+        assert!(svm.per_class_mean_margin(&inputs, &labels[.. 10]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn precomputed_kernel() -> Result<(), Error> {
+        // Same as `SAMPLE_MODEL`, but referring to its support vectors as training samples 1 and 2.
+        let model = SAMPLE_MODEL
+            .replace("kernel_type linear", "kernel_type precomputed")
+            .replace("0:0.0001 1:0.0001 2:0.0001 3:0.0001", "0:1")
+            .replace("0:1.2974607 1:1.0227317 2:1.2545854", "0:2");
+
+        let linear = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let precomputed = DenseSVM::try_from(model.as_str())?;
+        let sparse = SparseSVM::try_from(model.as_str())?;
+
+        let samples = [[0.0001, 0.0001, 0.0001, 0.0001], [1.2974607, 1.0227317, 1.2545854, 0.0]];
+        let features = [0.1, 0.2, 0.05, 0.3];
+
+        // Linear kernel values between the features and both training samples.
+        let kernel_values = samples.iter().map(|s| s.iter().zip(&features).map(|(a, b)| a * b).sum::<f32>()).collect::<Vec<_>>();
+
+        let mut problem = Problem::from(&linear);
+        let mut problem_precomputed = Problem::from(&precomputed);
+        let mut problem_sparse = Problem::from(&sparse);
+
+        problem.features().as_slice_mut()[.. 4].copy_from_slice(&features);
+        problem_precomputed.features().as_slice_mut()[1 .. 3].copy_from_slice(&kernel_values);
+        problem_sparse.features()[1] = kernel_values[0];
+        problem_sparse.features()[2] = kernel_values[1];
+
+        linear.predict_value(&mut problem)?;
+        precomputed.predict_value(&mut problem_precomputed)?;
+        sparse.predict_value(&mut problem_sparse)?;
+
+        assert_eq!(precomputed.attributes(), 3);
+        assert_eq!(precomputed.kernel_info(), KernelInfo::Precomputed);
+        assert!((problem.decision_values[(0, 1)] - problem_precomputed.decision_values[(0, 1)]).abs() < 1e-5);
+        assert!((problem.decision_values[(0, 1)] - problem_sparse.decision_values[(0, 1)]).abs() < 1e-5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_replaced_kernel() -> Result<(), Error> {
+        let linear = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let rbf = linear.with_replaced_kernel(Box::new(Rbf { gamma: 0.5 }));
+
+        let features = [0.5, 0.25, 0.125, 0.0];
+        let mut problem = Problem::from(&rbf);
+
+        problem.features().as_slice_mut()[.. 4].copy_from_slice(&features);
+        rbf.predict_value(&mut problem)?;
+
+        // Second support vector, the only one in class index 1.
+        let sv = [1.2974607, 1.0227317, 1.2545854, 0.0];
+        let distance = sv.iter().zip(&features).map(|(a, b)| (a - b) * (a - b)).sum::<f32>();
+
+        assert!((problem.kernel_values().flat()[(1, 0)] - f64::from((-0.5 * distance).exp())).abs() < 1e-6);
+        assert_eq!(rbf.kernel_info(), KernelInfo::Custom);
+        assert_eq!(linear.kernel_info(), KernelInfo::Linear);
+
+        Ok(())
+    }
+
+    #[test]
+    fn top_k() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.4;
+        }
+
+        svm.predict_probability(&mut problem)?;
+
+        let top = problem.top_k(&svm, 3)?;
+
+        assert_eq!(top.len(), 3);
+        assert_eq!(Solution::Label(top[0].0), problem.solution());
+        assert!(top[0].1 >= top[1].1 && top[1].1 >= top[2].1);
+        assert_eq!(problem.top_k(&svm, 100)?.len(), 8);
+
+        let plain = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let plain_problem = Problem::from(&plain);
+
+        match plain_problem.top_k(&plain, 3) {
+            Err(Error::NoProbabilities) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_self_consistency() -> Result<(), Error> {
+        DenseSVM::try_from(SAMPLE_MODEL)?.verify_self_consistency()?;
+        DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?.verify_self_consistency()?;
+        DenseSVM::try_from(include_str!("../../../tests/data_dense/m_e_svr_rbf_prob.libsvm"))?.verify_self_consistency()?;
+
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        svm.num_total_sv += 1;
+        assert!(svm.verify_self_consistency().is_err());
+
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        svm.rho = Triangular::with_dimension(3, 0.0);
+        assert!(svm.verify_self_consistency().is_err());
+
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        svm.classes[0].num_support_vectors -= 1;
+        assert!(svm.verify_self_consistency().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_finite() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        problem.validate_finite()?;
+
+        problem.features()[2] = std::f32::NAN;
+
+        match problem.validate_finite() {
+            Err(Error::NonFiniteFeature { index: 2, .. }) => {}
+            other => panic!("Expected Error::NonFiniteFeature, got {:?}", other),
+        }
+
+        problem.features()[2] = 0.0;
+        problem.features()[1] = std::f32::INFINITY;
+
+        assert!(problem.validate_finite().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decision_values_iter() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3;
+        }
+
+        svm.predict_value(&mut problem)?;
+
+        let values = problem.decision_values_iter().collect::<Vec<_>>();
+
+        assert_eq!(values.len(), 8 * 7 / 2);
+        assert_eq!(values[0].0, (0, 1));
+        assert_eq!(values[7].0, (1, 2));
+        assert!(values.iter().all(|&((i, j), value)| problem.decision_value(i, j) == Some(value)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn regression_confidence_interval() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_e_svr_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3;
+        }
+
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.regression_confidence_interval(0.9), None);
+
+        svm.predict_probability(&mut problem)?;
+
+        let value = problem.regression_value().unwrap();
+        let (lower, upper) = problem.regression_confidence_interval(0.9).unwrap();
+
+        // sigma = probA = 0.320473, half width = -sigma * ln(0.1)
+        assert!((upper - value - 0.737916).abs() < 1e-5);
+        assert!((value - lower - 0.737916).abs() < 1e-5);
+        assert_eq!(problem.regression_confidence_interval(0.0), Some((value, value)));
+        assert_eq!(problem.regression_confidence_interval(1.0), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_to_dense() -> Result<(), Error> {
+        let model = include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+        let expected = DenseSVM::try_from(model)?;
+        let mut sparse = SparseSVM::try_from(model)?;
+
+        sparse.set_metadata("version", "3");
+        sparse.set_precision_mode(PrecisionMode::Precise);
+
+        let dense = sparse.to_dense()?;
+        let mut problem_expected = Problem::from(&expected);
+        let mut problem = Problem::from(&dense);
+
+        for i in 0 .. dense.attributes() {
+            problem_expected.features()[i] = 0.3;
+            problem.features()[i] = 0.3;
+        }
+
+        expected.predict_probability(&mut problem_expected)?;
+        dense.predict_probability(&mut problem)?;
+
+        assert_eq!(problem.solution(), problem_expected.solution());
+        assert_eq!(problem.probabilities(), problem_expected.probabilities());
+        assert_eq!(dense.get_metadata("version"), Some("3"));
+        assert_eq!(dense.precision, PrecisionMode::Precise);
+        assert_eq!(dense.to_string(), expected.to_string());
+
+        let too_sparse = SparseSVM::try_from("svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0\nlabel 0 1\nnr_sv 1 1\nSV\n1 0:1 99:1\n-1 5:1\n")?;
+
+        match too_sparse.to_dense() {
+            Err(Error::TooSparse { density }) => assert!((density - 0.015).abs() < 1e-9),
+            other => panic!("Expected Error::TooSparse, got {:?}", other.err()),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn kernel_precision() -> Result<(), Error> {
+        let model = include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+        let single = DenseSVM::try_from(model)?;
+        let mut double = DenseSVM::try_from(model)?;
+
+        double.set_kernel_precision(KernelPrecision::Double);
+
+        let mut problem_single = Problem::from(&single);
+        let mut problem_double = Problem::from(&double);
+
+        for i in 0 .. single.attributes() {
+            problem_single.features()[i] = 0.3 + i as f32 * 0.01;
+            problem_double.features()[i] = 0.3 + i as f32 * 0.01;
+        }
+
+        single.predict_value(&mut problem_single)?;
+        double.predict_value(&mut problem_double)?;
+
+        let gamma = match single.kernel_info() {
+            KernelInfo::Rbf { gamma } => gamma,
+            other => panic!("Unexpected kernel {:?}", other),
+        };
+
+        let support_vectors = double.classes[0].support_vectors_f64.as_ref().unwrap().flat();
+        let distance = (0 .. single.attributes())
+            .map(|j| (support_vectors[(0, j)] - f64::from(0.3 + j as f32 * 0.01)).powi(2))
+            .sum::<f64>();
+        let expected = (-gamma * distance).exp();
+
+        assert!((problem_double.kernel_values().flat()[(0, 0)] - expected).abs() < 1e-12);
+        assert!((problem_single.kernel_values().flat()[(0, 0)] - expected).abs() < 1e-5);
+        assert_eq!(problem_single.solution(), problem_double.solution());
+
+        Ok(())
+    }
+
+    #[test]
+    fn kernel_precision_borderline() -> Result<(), Error> {
+        // For `x = (1, 1)` libSVM computes the decision value `16777217 - 16777216 - 0.5 = 0.5` in
+        // `f64` and predicts `1`. In `f32` the first kernel value rounds to `16777216`, so the
+        // decision value becomes `-0.5` and the label flips to `-1`.
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0.5\nlabel 1 -1\nnr_sv 1 1\nSV\n1 0:16777216 1:1\n-1 0:16777216 1:0\n";
+        let single = DenseSVM::try_from(model)?;
+        let mut double = DenseSVM::try_from(model)?;
+        let mut fast = DenseSVM::try_from(model)?;
+
+        double.set_kernel_precision(KernelPrecision::Double);
+        fast.enable_linear_fast()?;
+
+        for (svm, decision_value, label) in &[(&single, -0.5, -1), (&double, 0.5, 1), (&fast, 0.5, 1)] {
+            let mut problem = Problem::from(*svm);
+
+            problem.features().as_slice_mut().clone_from_slice(&[1.0, 1.0]);
+            svm.predict_value(&mut problem)?;
+
+            assert_eq!(problem.decision_value(0, 1), Some(*decision_value));
+            assert_eq!(problem.solution(), Solution::Label(*label));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn kernel_precision_borderline_rbf() -> Result<(), Error> {
+        // Written by `tests/gen-borderline.py`, which also computes libSVM's decision value. Both
+        // support vectors round to the same `f32`, so `Single` sees two equal kernel values and
+        // ends up at `-rho`.
+        let model = "svm_type c_svc\nkernel_type rbf\ngamma 0.5\nnr_class 2\ntotal_sv 2\nrho 9e-10\nlabel 1 -1\nnr_sv 1 1\nSV\n1.0 1:0.30000002 2:0.6 3:0.9\n-1.0 1:0.30000001 2:0.6 3:0.9\n";
+        let libsvm_decision_value = 9.232_982_901_267_292e-10;
+
+        let single = DenseSVM::try_from(model)?;
+        let mut double = DenseSVM::try_from(model)?;
+
+        double.set_kernel_precision(KernelPrecision::Double);
+
+        let mut problem_single = Problem::from(&single);
+        let mut problem_double = Problem::from(&double);
+
+        problem_single.features().as_slice_mut().clone_from_slice(&[0.5, 0.25, 0.75]);
+        problem_double.features().as_slice_mut().clone_from_slice(&[0.5, 0.25, 0.75]);
+
+        single.predict_value(&mut problem_single)?;
+        double.predict_value(&mut problem_double)?;
+
+        assert!((problem_double.decision_value(0, 1).unwrap() - libsvm_decision_value).abs() < 1e-14);
+        assert_eq!(problem_double.solution(), Solution::Label(1));
+
+        assert_eq!(problem_single.decision_value(0, 1), Some(-9e-10));
+        assert_eq!(problem_single.solution(), Solution::Label(-1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn feature_contributions() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3;
+        }
+
+        // Push one feature far away from everything.
+        problem.features()[5] = 5.0;
+
+        svm.predict_value(&mut problem)?;
+
+        let contributions = problem.feature_contributions(&svm)?;
+        let largest = contributions.iter().cloned().fold(0.0, f64::max);
+
+        assert_eq!(contributions.len(), svm.attributes());
+        assert!(contributions.iter().all(|c| *c >= 0.0));
+        assert_eq!(contributions[5], largest);
+
+        match problem.feature_contributions(&DenseSVM::try_from(SAMPLE_MODEL)?) {
+            Err(Error::NotRbf) => {}
+            other => panic!("Expected Error::NotRbf, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn support_vector_counts() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let per_class = (0 .. svm.classes()).map(|i| svm.support_vectors_for_class(i).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(per_class.iter().sum::<usize>(), svm.total_support_vectors());
+        assert_eq!(svm.support_vectors_for_class(svm.classes()), None);
+
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        assert_eq!(svm.support_vectors_for_class(0), Some(1));
+        assert_eq!(svm.total_support_vectors(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_features() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+        let mut expected = Problem::from(&svm);
+
+        expected.features()[0] = 0.23122;
+        expected.features()[3] = 0.5;
+
+        problem.features()[1] = 1.0;
+        problem.set_features_sparse(&[(0, 0.23122), (3, 0.5)])?;
+
+        svm.predict_value(&mut problem)?;
+        svm.predict_value(&mut expected)?;
+
+        assert_eq!(problem.solution(), expected.solution());
+        assert_eq!(problem.decision_value(0, 1), expected.decision_value(0, 1));
+
+        problem.set_feature(2, 0.1)?;
+
+        match problem.set_feature(4, 0.1) {
+            Err(Error::InvalidFeatureIndex { index: 4, attributes: 4 }) => {}
+            other => panic!("Expected Error::InvalidFeatureIndex, got {:?}", other),
+        }
+
+        assert!(problem.set_features_sparse(&[(1, 0.5), (7, 0.5)]).is_err());
+        assert_eq!(problem.features()[1], 0.0);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_kernels_match_serial() -> Result<(), Error> {
+        let model = include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+        let serial = DenseSVM::try_from(model)?;
+        let mut parallel = DenseSVM::try_from(model)?;
+
+        parallel.set_parallel_kernels(true);
+
+        let mut problem = Problem::from(&serial);
+        let mut problem_parallel = Problem::from(&parallel);
+
+        for i in 0 .. serial.attributes() {
+            problem.features()[i] = 0.3 + i as f32 * 0.1;
+            problem_parallel.features()[i] = 0.3 + i as f32 * 0.1;
+        }
+
+        serial.predict_probability(&mut problem)?;
+        parallel.predict_probability(&mut problem_parallel)?;
+
+        for i in 0 .. serial.classes() {
+            assert_eq!(problem.kernel_values.row_as_flat(i), problem_parallel.kernel_values.row_as_flat(i));
+        }
+
+        assert_eq!(problem.decision_values.data, problem_parallel.decision_values.data);
+        assert_eq!(problem.probabilities(), problem_parallel.probabilities());
+        assert_eq!(problem.solution(), problem_parallel.solution());
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_rho() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        problem.features()[0] = 0.23122;
+
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(42));
+
+        svm.set_rho(0, 1, -0.35)?;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(21));
+
+        assert!(svm.set_rho(1, 0, 0.0).is_err());
+        assert!(svm.set_rho(0, 2, 0.0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn active_support_vectors() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        // Right next to the support vectors of the first classes.
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.0001;
+        }
+
+        svm.predict_value(&mut problem)?;
+        let near = svm.active_support_vectors(&problem, 0.5);
+
+        // Far away from all training data.
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 100.0;
+        }
+
+        svm.predict_value(&mut problem)?;
+        let far = svm.active_support_vectors(&problem, 0.5);
+
+        assert!(near >= 8);
+        assert_eq!(far, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decision_without_sv() -> Result<(), Error> {
+        let model = include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+        let svm = DenseSVM::try_from(model)?;
+        let mut zeroed = DenseSVM::try_from(model)?;
+
+        // Remove support vector 1 of class 2 from the pair (2, 5).
+        zeroed.classes[2].coefficients.flat_mut()[(4, 1)] = 0.0;
+
+        let mut problem = Problem::from(&svm);
+        let mut problem_zeroed = Problem::from(&zeroed);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.5;
+            problem_zeroed.features()[i] = 0.5;
+        }
+
+        svm.predict_value(&mut problem)?;
+        zeroed.predict_value(&mut problem_zeroed)?;
+
+        let estimate = svm.decision_without_sv(&problem, 2, 1, (2, 5))?;
+
+        assert!((estimate - problem_zeroed.decision_values[(2, 5)]).abs() < 1e-6);
+        assert!(svm.decision_without_sv(&problem, 3, 1, (2, 5)).is_err());
+        assert!(svm.decision_without_sv(&problem, 2, 1, (5, 2)).is_err());
+
+        let num_sv = svm.support_vectors_for_class(2).unwrap();
+
+        match svm.decision_without_sv(&problem, 2, num_sv, (2, 5)) {
+            Err(Error::InvalidSupportVector { class: 2, index }) => assert_eq!(index, num_sv),
+            other => panic!("Expected Error::InvalidSupportVector, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn predict_from_kernel_values() -> Result<(), Error> {
+        use simd_aligned::SimdMatrix;
+
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+
+        let mut problem = Problem::from(&svm);
+        problem.features().as_slice_mut().clone_from_slice(&[0.4, 0.4, 0.4, 0.4, 0.4, 0.4, 0.4, 0.4]);
+        svm.predict_value(&mut problem)?;
+
+        let mut external = Problem::from(&svm);
+        *external.kernel_values_mut() = problem.kernel_values().clone();
+
+        assert_eq!(problem.solution(), Solution::Label(svm.predict_from_kernel_values(&mut external)?));
+
+        let other = DenseSVM::try_from(SAMPLE_MODEL)?;
+        assert!(other.predict_from_kernel_values(&mut external).is_err());
+
+        // Right number of classes, but rows too short for the support vectors of each class.
+        let mut truncated = Problem::from(&svm);
+        *truncated.kernel_values_mut() = SimdMatrix::with_dimension(svm.classes(), 1);
+
+        match svm.predict_from_kernel_values(&mut truncated) {
+            Err(Error::DimensionMismatch { actual: 1, .. }) => {}
+            other => panic!("Expected Error::DimensionMismatch, got {:?}", other),
+        }
+
+        // Kernel values of the other model, which has fewer classes.
+        let mut mismatched = Problem::from(&svm);
+        *mismatched.kernel_values_mut() = Problem::from(&other).kernel_values().clone();
+
+        match svm.predict_from_kernel_values(&mut mismatched) {
+            Err(Error::DimensionMismatch { expected, actual: 2 }) => assert_eq!(expected, svm.classes()),
+            other => panic!("Expected Error::DimensionMismatch, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_polynomial_kernel() -> Result<(), Error> {
+        let mut raw_model = ModelFile::random_dense("c_svc", "polynomial", 16, 8);
+        let svm = DenseSVM::try_from(&raw_model)?;
+        let mut problem = Problem::from(&svm);
+
+        svm.predict_value(&mut problem)?;
+
+        match problem.solution() {
+            Solution::Label(label) => assert!(label == 0 || label == 1),
+            other => panic!("Unexpected solution {:?}", other),
+        }
+
+        raw_model.header.degree = None;
+
+        match DenseSVM::try_from(&raw_model) {
+            Err(Error::NoDegree) => {}
+            _ => panic!("Expected Error::NoDegree"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_sigmoid_kernel() -> Result<(), Error> {
+        let mut raw_model = ModelFile::random_dense("c_svc", "sigmoid", 16, 8);
+        let svm = DenseSVM::try_from(&raw_model)?;
+        let mut problem = Problem::from(&svm);
+
+        svm.predict_value(&mut problem)?;
+
+        match problem.solution() {
+            Solution::Label(label) => assert!(label == 0 || label == 1),
+            other => panic!("Unexpected solution {:?}", other),
+        }
+
+        raw_model.header.coef0 = None;
+
+        match DenseSVM::try_from(&raw_model) {
+            Err(Error::NoCoef0) => {}
+            _ => panic!("Expected Error::NoCoef0"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn pseudo_probabilities() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        problem.features().as_slice_mut().clone_from_slice(&[1.2974607, 1.0227317, 1.2545854, 0.0]);
+
+        let probabilities = svm.predict_pseudo_probability(&mut problem)?;
+        let winner = crate::util::find_max_index(&probabilities);
+
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(problem.solution(), Solution::Label(svm.class_label_for_index(winner).unwrap()));
+
+        // With 8 classes, summed decision values and votes often disagree; the most likely class
+        // must still be the predicted one.
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf.libsvm"))?;
+        let mut rng = StdRng::seed_from_u64(507);
+        let mut problem = Problem::from(&svm);
+
+        for _ in 0 .. 200 {
+            for i in 0 .. svm.attributes() {
+                problem.features()[i] = rng.gen_range(-1.0, 1.0);
+            }
+
+            let probabilities = svm.predict_pseudo_probability(&mut problem)?;
+            let winner = crate::util::find_max_index(&probabilities);
+
+            assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert_eq!(problem.solution(), Solution::Label(svm.class_label_for_index(winner).unwrap()));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn model_string_round_trip() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let model = svm.to_string();
+        let reloaded = DenseSVM::try_from(model.as_str())?;
+
+        let mut problem = Problem::from(&svm);
+        let mut problem_reloaded = Problem::from(&reloaded);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3;
+            problem_reloaded.features()[i] = 0.3;
+        }
+
+        svm.predict_probability(&mut problem)?;
+        reloaded.predict_probability(&mut problem_reloaded)?;
+
+        assert_eq!(problem.solution(), problem_reloaded.solution());
+        assert_eq!(problem.probabilities(), problem_reloaded.probabilities());
+        assert_eq!(reloaded.to_string(), model);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn predict_jsonl() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let input = "0.0001, 0.0001, 0.0001, 0.0001\n\n1.2974607,1.0227317,1.2545854,0\n";
+        let mut output = Vec::new();
+
+        assert_eq!(svm.predict_jsonl(input.as_bytes(), &mut output)?, 2);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["label"], 21);
+        assert_eq!(lines[1]["label"], 42);
+        assert!(lines.iter().all(|l| l["confidence"].is_f64()));
+
+        match svm.predict_jsonl("0,0,0,0\n\n1,2,3\n".as_bytes(), Vec::new()) {
+            Err(Error::CsvRow { line: 3, .. }) => {}
+            other => panic!("Expected Error::CsvRow, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn predict_csv() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let input = "0.0001, 0.0001, 0.0001, 0.0001\n\n1.2974607,1.0227317,1.2545854,0\n";
+        let mut output = Vec::new();
+
+        assert_eq!(svm.predict_csv(input.as_bytes(), &mut output)?, 2);
+        assert_eq!(String::from_utf8(output).unwrap(), "21\n42\n");
+
+        match svm.predict_csv("0,0,0,0\n\n1,2,3\n".as_bytes(), Vec::new()) {
+            Err(Error::CsvRow { line: 3, .. }) => {}
+            other => panic!("Expected Error::CsvRow, got {:?}", other),
+        }
+
+        match svm.predict_csv("0,0,x,0\n".as_bytes(), Vec::new()) {
+            Err(Error::CsvRow { line: 1, .. }) => {}
+            other => panic!("Expected Error::CsvRow, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_file() -> Result<(), Error> {
+        let svm = DenseSVM::from_file("tests/data_dense/m_csvm_rbf_prob.libsvm")?;
+
+        assert_eq!(svm.classes(), 8);
+
+        match DenseSVM::from_file("tests/data_dense/does_not_exist.libsvm") {
+            Err(Error::Io(_)) => {}
+            _ => panic!("Expected Error::Io"),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn from_mmap() -> Result<(), Error> {
+        let raw_model = ModelFile::random_dense("c_svc", "rbf", 4096, 64);
+        let model = DenseSVM::try_from(&raw_model)?.to_string();
+
+        let path = std::env::temp_dir().join(format!("ffsvm_from_mmap_{}.libsvm", std::process::id()));
+        std::fs::write(&path, &model)?;
+
+        let mapped = DenseSVM::from_mmap(&path);
+        std::fs::remove_file(&path)?;
+
+        let mapped = mapped?;
+        let loaded = DenseSVM::try_from(model.as_str())?;
+
+        let mut problem_mapped = Problem::from(&mapped);
+        let mut problem_loaded = Problem::from(&loaded);
+
+        for i in 0 .. 64 {
+            problem_mapped.features()[i] = i as f32 / 64.0;
+            problem_loaded.features()[i] = i as f32 / 64.0;
+        }
+
+        mapped.predict_value(&mut problem_mapped)?;
+        loaded.predict_value(&mut problem_loaded)?;
+
+        assert_eq!(problem_mapped.solution(), problem_loaded.solution());
+        assert_eq!(problem_mapped.decision_values.data, problem_loaded.decision_values.data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn coefficient_matrix_shape() {
+        let model = SAMPLE_MODEL.replace("-1 0:1.2974607", "-1 0.5 0:1.2974607");
+
+        match DenseSVM::try_from(model.as_str()) {
+            Err(Error::CoefficientMatrixShape { vector_index, expected, actual }) => {
+                assert_eq!(vector_index, 1);
+                assert_eq!(expected, 1);
+                assert_eq!(actual, 2);
+            }
+            _ => panic!("Expected Error::CoefficientMatrixShape"),
+        }
+    }
+
+    #[test]
+    fn malformed_model() {
+        let cases = [
+            ("label 21 42", "label 21", "label"),
+            ("nr_sv 1 1", "nr_sv 1", "nr_sv"),
+            ("nr_sv 1 1", "nr_sv 1 2", "nr_sv"),
+            ("rho -0.25527", "rho -0.25527 1", "rho"),
+            ("total_sv 2", "total_sv 3", "total_sv"),
+            ("nr_sv", "probA -3.09609 1\nprobB -0.368652\nnr_sv", "probA"),
+            ("nr_sv", "probA -3.09609\nnr_sv", "probB"),
+            ("kernel_type linear", "kernel_type chi2", "kernel_type"),
+        ];
+
+        for (from, to, expected) in cases.iter() {
+            let model = SAMPLE_MODEL.replace(from, to);
+
+            match DenseSVM::try_from(model.as_str()) {
+                Err(Error::MalformedModel { field }) => assert_eq!(field, *expected),
+                other => panic!("Expected Error::MalformedModel for `{}`, got {:?}", to, other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn empty_model() {
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 0\nrho 0\nlabel 0 1\nnr_sv 0 0\nSV\n";
+
+        match DenseSVM::try_from(model) {
+            Err(Error::EmptyModel) => {}
+            _ => panic!("Expected Error::EmptyModel"),
+        }
+    }
+
+    #[test]
+    fn insufficient_classes() {
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 1\ntotal_sv 1\nrho\nlabel 1\nnr_sv 1\nSV\n1 0:0.5\n";
+
+        match DenseSVM::try_from(model) {
+            Err(Error::InsufficientClasses { found: 1 }) => {}
+            other => panic!("Expected Error::InsufficientClasses, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn problem_reset() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.5;
+        }
+
+        svm.predict_probability(&mut problem)?;
+        let solution = problem.solution();
+
+        problem.reset();
+
+        assert_eq!(problem.solution(), Solution::None);
+        assert!(problem.probabilities().iter().all(|p| *p == 0.0));
+        assert!(problem.vote.iter().all(|v| *v == 0));
+
+        svm.predict_probability(&mut problem)?;
+        assert_eq!(problem.solution(), solution);
+
+        Ok(())
+    }
+
+    #[test]
+    fn probability_iterations() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.5;
+        }
+
+        svm.predict_probability(&mut problem)?;
+
+        assert!(problem.probability_iterations() >= 1);
+        assert!(problem.probability_iterations() <= 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decision_value() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.5;
+        }
+
+        svm.predict_value(&mut problem)?;
+
+        assert_eq!(problem.decision_value(1, 3), Some(problem.decision_values[(1, 3)]));
+        assert_eq!(problem.decision_value(3, 1), Some(-problem.decision_values[(1, 3)]));
+        assert_eq!(problem.decision_value(2, 2), None);
+        assert_eq!(problem.decision_value(0, 8), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn classify_slice() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let features: [f32; 8] = [1.28, 0.98, 1.48, 1.12, 0.89, 1.16, 0.92, 1.14];
+
+        let mut problem = Problem::from(&svm);
+        problem.features().as_slice_mut()[.. 8].copy_from_slice(&features);
+        svm.predict_value(&mut problem)?;
+
+        let mut reused = Problem::from(&svm);
+
+        assert_eq!(problem.solution(), Solution::Label(svm.classify_slice(&mut reused, &features)?));
+        assert!(svm.classify_slice(&mut reused, &features[.. 4]).is_err());
+
+        assert_eq!(problem.solution(), Solution::Label(svm.predict_label(&features)?));
+        assert!(svm.predict_label(&features[.. 4]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn labeled_probabilities() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.5;
+        }
+
+        svm.predict_probability(&mut problem)?;
+        let labeled = svm.labeled_probabilities(&problem)?;
+
+        assert_eq!(labeled.len(), svm.classes());
+        assert!(labeled.windows(2).all(|w| w[0].0 < w[1].0));
+
+        for (label, probability) in labeled {
+            let index = svm.class_index_for_label(label).unwrap();
+            assert_eq!(probability, problem.probabilities()[index]);
+        }
+
+        let plain = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let problem = Problem::from(&plain);
+
+        match plain.labeled_probabilities(&problem) {
+            Err(Error::NoProbabilities) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn metadata() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        assert_eq!(svm.get_metadata("version"), None);
+
+        svm.set_metadata("version", "1");
+        svm.set_metadata("version", "2");
+        svm.set_metadata("features", "x,y,z,w");
+
+        assert_eq!(svm.get_metadata("version"), Some("2"));
+        assert_eq!(svm.get_metadata("features"), Some("x,y,z,w"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_rbf_gamma() -> Result<(), Error> {
+        let mut raw_model = ModelFile::random_dense_multiclass("c_svc", "rbf", 3, 8, 5);
+        let mut svm = DenseSVM::try_from(&raw_model)?;
+        let mut problem = Problem::from(&svm);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3 + i as f32 * 0.1;
+        }
+
+        raw_model.header.gamma = Some(0.75);
+        let expected = DenseSVM::try_from(&raw_model)?;
+
+        svm.set_rbf_gamma(0.75)?;
+        svm.predict_value(&mut problem)?;
+        let actual = problem.decision_values.data.clone();
+
+        expected.predict_value(&mut problem)?;
+
+        assert_eq!(svm.kernel_info(), KernelInfo::Rbf { gamma: 0.75 });
+        assert_eq!(actual, problem.decision_values.data);
+
+        let mut linear = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        match linear.set_rbf_gamma(0.75) {
+            Err(Error::NotRbf) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        assert_eq!(linear.kernel_info(), KernelInfo::Linear);
+
+        Ok(())
+    }
+
+    #[test]
+    fn kernel_info() -> Result<(), Error> {
+        let rbf = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let linear = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        match rbf.kernel_info() {
+            KernelInfo::Rbf { gamma } => assert!((gamma - 0.142857).abs() < 1e-6),
+            other => panic!("Unexpected kernel {:?}", other),
+        }
+
+        assert_eq!(linear.kernel_info(), KernelInfo::Linear);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_inputs() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        let rows = vec![
+            vec![0.1, 0.2, 0.3, 0.4],
+            vec![0.1, 0.2, 0.3],
+            vec![0.1, std::f32::NAN, 0.3, 0.4],
+            vec![0.1, 0.2, 0.3, 0.4],
+            vec![0.1, 0.2, 0.3, std::f32::INFINITY],
+        ];
+
+        let errors = svm.validate_inputs(&rows);
+
+        assert_eq!(errors.len(), 3);
+
+        match errors[0] {
+            (1, Error::DimensionMismatch { expected: 4, actual: 3 }) => {}
+            ref other => panic!("Unexpected error {:?}", other),
+        }
+
+        match errors[1] {
+            (2, Error::NonFiniteFeature { index: 1, .. }) => {}
+            ref other => panic!("Unexpected error {:?}", other),
+        }
+
+        match errors[2] {
+            (4, Error::NonFiniteFeature { index: 3, .. }) => {}
+            ref other => panic!("Unexpected error {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn probabilities_require_probability_model() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        match svm.predict_probability(&mut problem) {
+            Err(Error::NoProbabilities) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        // Nothing must have been computed.
+        assert_eq!(problem.solution(), Solution::None);
+        assert!(problem.probabilities().iter().all(|p| *p == 0.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn calibrate_threshold_for_fpr() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        // Label 21 is the positive class (index 0), label 42 the negative one.
+        let mut inputs = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0 .. 200 {
+            inputs.push(vec![i as f32 / 200.0, 0.3, 0.2, 0.1]);
+            labels.push(if i % 4 == 0 { 21 } else { 42 });
+        }
+
+        let threshold = svm.calibrate_threshold_for_fpr(&inputs, &labels, 0.1)?;
+        let rho = svm.rho[(0, 1)];
+
+        svm.set_rho(0, 1, rho + threshold)?;
+
+        let mut problem = Problem::from(&svm);
+        let mut false_positives = 0;
+        let mut negatives = 0;
+
+        for (row, _) in inputs.iter().zip(&labels).filter(|(_, label)| **label == 42) {
+            problem.features().as_slice_mut()[.. 4].copy_from_slice(row);
+            svm.predict_value(&mut problem)?;
+
+            negatives += 1;
+
+            if problem.solution() == Solution::Label(21) {
+                false_positives += 1;
+            }
+        }
+
+        let fpr = f64::from(false_positives) / f64::from(negatives);
+
+        assert!((fpr - 0.1).abs() < 0.02, "fpr was {}", fpr);
+        assert!(svm.calibrate_threshold_for_fpr(&inputs, &labels[.. 10], 0.1).is_err());
+
+        labels[7] = 43;
+
+        match svm.calibrate_threshold_for_fpr(&inputs, &labels, 0.1) {
+            Err(Error::UnknownLabel { label: 43 }) => {}
+            other => panic!("Expected Error::UnknownLabel, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn widest_support_vector_not_first() -> Result<(), Error> {
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0\nlabel 0 1\nnr_sv 1 1\nSV\n1 0:0.5 1:0.5\n-1 0:0.1 1:0.1 2:0.1 3:2\n";
+        let svm = DenseSVM::try_from(model)?;
+        let mut problem = Problem::from(&svm);
+
+        assert_eq!(svm.attributes(), 4);
+
+        // Only the last attribute is set, which only the second support vector has.
+        problem.features()[3] = 1.0;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn problem_from_other_model() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let other = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&other);
+
+        match svm.predict_value(&mut problem) {
+            Err(Error::DimensionMismatch { expected: 4, actual: 8 }) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    /// Predicts `inputs` with [Predict::predict_batch_parallel] once per thread count and returns
+    /// `true` if all runs produced bit-identical solutions and decision values.
+    #[cfg(feature = "parallel")]
+    fn verify_determinism(svm: &DenseSVM, inputs: &[Vec<f32>], threads: &[usize]) -> bool {
+        let mut runs = Vec::new();
+
+        for &num_threads in threads {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap();
+
+            let mut problems = inputs
+                .iter()
+                .map(|row| {
+                    let mut problem = Problem::from(svm);
+                    problem.features().as_slice_mut()[.. row.len()].copy_from_slice(row);
+                    problem
+                })
+                .collect::<Vec<_>>();
+
+            pool.install(|| svm.predict_batch_parallel(&mut problems)).unwrap();
+
+            let results = problems
+                .iter()
+                .map(|problem| (problem.solution(), problem.decision_values.data.iter().map(|x| x.to_bits()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+
+            runs.push(results);
+        }
+
+        runs.windows(2).all(|w| w[0] == w[1])
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn deterministic_across_threads() -> Result<(), Error> {
+        let models = [
+            include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_csvm_linear_prob.libsvm"),
+        ];
+
+        for model in models.iter() {
+            let svm = DenseSVM::try_from(*model)?;
+
+            let inputs = (0 .. 500)
+                .map(|i| (0 .. svm.attributes()).map(|a| ((i * 7 + a * 13) % 100) as f32 / 50.0).collect())
+                .collect::<Vec<Vec<f32>>>();
+
+            assert!(verify_determinism(&svm, &inputs, &[1, 2, 4, 8]));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn problem_from_features() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        let mut problem = Problem::from_features(&svm, &[0.23122, 0.0, 0.0, 0.0])?;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(42));
+
+        assert!(Problem::from_features(&svm, &[0.1, 0.2]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn linear_fast() -> Result<(), Error> {
+        let model = include_str!("../../../tests/data_dense/m_csvm_linear_prob.libsvm");
+        let svm = DenseSVM::try_from(model)?;
+        let mut double = DenseSVM::try_from(model)?;
+        let mut fast = DenseSVM::try_from(model)?;
+
+        double.set_kernel_precision(KernelPrecision::Double);
+        fast.enable_linear_fast()?;
+
+        let mut problem = Problem::from(&svm);
+        let mut problem_double = Problem::from(&double);
+        let mut problem_fast = Problem::from(&fast);
+
+        for step in 0 .. 100 {
+            for i in 0 .. svm.attributes() {
+                let x = ((step * 7 + i * 13) % 100) as f32 / 50.0;
+                problem.features()[i] = x;
+                problem_double.features()[i] = x;
+                problem_fast.features()[i] = x;
+            }
+
+            svm.predict_value(&mut problem)?;
+            double.predict_value(&mut problem_double)?;
+            fast.predict_value(&mut problem_fast)?;
+
+            assert_eq!(problem.solution(), problem_fast.solution());
+            assert_eq!(problem_double.solution(), problem_fast.solution());
+
+            // The fast path computes in `f64`, so it only differs from `Single` by `f32` rounding,
+            // and from `Double` by the order of summation.
+            for (a, b) in problem.decision_values.data.iter().zip(&problem_fast.decision_values.data) {
+                assert!((a - b).abs() < 1e-4);
+            }
+
+            for (a, b) in problem_double.decision_values.data.iter().zip(&problem_fast.decision_values.data) {
+                assert!((a - b).abs() < 1e-9);
+            }
+        }
+
+        let mut rbf = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+
+        match rbf.enable_linear_fast() {
+            Err(Error::NotLinear) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn linear_fast_exact() -> Result<(), Error> {
+        // Model values are multiples of 1/4 and features multiples of 1/8, so every product and
+        // sum is exact in `f32` and `f64`, and both paths must produce identical decision values.
+        let mut model = String::from("svm_type c_svc\nkernel_type linear\nnr_class 3\ntotal_sv 6\nrho 0.5 -0.25 1\nlabel 1 2 3\nnr_sv 2 2 2\nSV\n");
+
+        for v in 0 .. 6 {
+            let coef = |k: usize| ((v * 5 + k * 3) % 9) as f32 / 4.0 - 1.0;
+
+            model.push_str(&format!("{} {}", coef(0), coef(1)));
+
+            for a in 0 .. 5 {
+                model.push_str(&format!(" {}:{}", a, ((v * 7 + a * 11) % 13) as f32 / 4.0 - 1.5));
+            }
+
+            model.push('\n');
+        }
+
+        let svm = DenseSVM::try_from(model.as_str())?;
+        let mut fast = DenseSVM::try_from(model.as_str())?;
+
+        fast.enable_linear_fast()?;
+
+        let mut problem = Problem::from(&svm);
+        let mut problem_fast = Problem::from(&fast);
+
+        for step in 0 .. 100 {
+            for i in 0 .. svm.attributes() {
+                let x = ((step * 7 + i * 13) % 40) as f32 / 8.0 - 2.5;
+                problem.features()[i] = x;
+                problem_fast.features()[i] = x;
+            }
+
+            svm.predict_value(&mut problem)?;
+            fast.predict_value(&mut problem_fast)?;
+
+            assert_eq!(problem.solution(), problem_fast.solution());
+            assert_eq!(problem.decision_values.data, problem_fast.decision_values.data);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn attributes_exclude_padding() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        assert_eq!(svm.attributes(), 8);
+        assert_eq!(problem.features().as_slice_mut().len(), svm.attributes());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn support_vector_padding() -> Result<(), Error> {
+        // 7 attributes never fill all SIMD lanes, so there is always padding.
+        let kernels = [
+            "kernel_type linear",
+            "kernel_type rbf\ngamma 0.5",
+            "kernel_type polynomial\ndegree 3\ngamma 0.5\ncoef0 0.25",
+            "kernel_type sigmoid\ngamma 0.5\ncoef0 0.25",
+        ];
+
+        for kernel in kernels.iter() {
+            for precision in &[KernelPrecision::Single, KernelPrecision::Double] {
+                let model = format!(
+                    "svm_type c_svc\n{}\nnr_class 2\ntotal_sv 2\nrho 0.1\nlabel 0 1\nnr_sv 1 1\nSV\n1 0:0.1 1:0.2 2:0.3 3:0.4 4:0.5 5:0.6 6:0.7\n-1 0:0.7 1:0.6 2:0.5 3:0.4 4:0.3 5:0.2 6:0.1\n",
+                    kernel
+                );
+                let mut svm = DenseSVM::try_from(model.as_str())?;
+
+                svm.set_kernel_precision(*precision);
+
+                for class in 0 .. svm.classes() {
+                    assert!(!svm.support_vector_padding(class, 0).is_empty());
+                    assert!(svm.support_vector_padding(class, 0).iter().all(|x| *x == 0.0));
+                }
+
+                let mut problem = Problem::from_features(&svm, &[0.9, 0.8, 0.1, 0.2, 0.3, 0.4, 0.5])?;
+                svm.predict_value(&mut problem)?;
+                let expected = problem.decision_values[(0, 1)];
+
+                // Poisoned (finite) padding must neither leak into dot products nor distances.
+                svm.poison_support_vector_padding(0, 0, 123.0);
+                svm.poison_support_vector_padding(1, 0, -42.0);
+                svm.predict_value(&mut problem)?;
+
+                assert!(svm.support_vector_padding(0, 0).iter().all(|x| *x == 123.0));
+                assert_eq!(problem.decision_values[(0, 1)], expected, "{} with {:?}", kernel, precision);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+        let settings = ProbabilitySettings {
+            max_iterations: 250,
+            epsilon: 0.004,
+        };
+
+        svm.set_precision_mode(PrecisionMode::Adaptive { epsilon: 0.5 });
+        svm.set_kernel_precision(KernelPrecision::Double);
+        svm.set_probability_settings(settings);
+        svm.set_probability_warm_start(true);
+        svm.apply_class_frequency_correction(&[10, 20, 30, 40, 50, 60, 70, 80])?;
+
+        let bytes = bincode::serialize(&svm).unwrap();
+        let loaded: DenseSVM = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(loaded.precision, PrecisionMode::Adaptive { epsilon: 0.5 });
+        assert_eq!(loaded.kernel_precision, KernelPrecision::Double);
+        assert_eq!(loaded.probability_settings(), settings);
+        assert!(loaded.probability_warm_start);
+
+        for (class, loaded_class) in svm.classes.iter().zip(&loaded.classes) {
+            assert_eq!(class.correction, loaded_class.correction);
+        }
+
+        let mut problem = Problem::from(&svm);
+        let mut problem_loaded = Problem::from(&loaded);
+
+        for i in 0 .. svm.attributes() {
+            problem.features()[i] = 0.3 + i as f32 * 0.1;
+            problem_loaded.features()[i] = 0.3 + i as f32 * 0.1;
+        }
+
+        svm.predict_probability(&mut problem)?;
+        loaded.predict_probability(&mut problem_loaded)?;
+
+        assert_eq!(problem.solution(), problem_loaded.solution());
+        assert_eq!(problem.probabilities(), problem_loaded.probabilities());
+        assert_eq!(problem.decision_values.data, problem_loaded.decision_values.data);
+
+        // Custom kernels can't be stored.
+        let custom = svm.with_replaced_kernel(Box::new(Rbf { gamma: 0.5 }));
+        assert!(bincode::serialize(&custom).is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_metadata() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        svm.set_metadata("version", "1.2.0");
+        svm.set_metadata("features", "height,width,depth,weight");
+
+        let loaded: DenseSVM = bincode::deserialize(&bincode::serialize(&svm).unwrap()).unwrap();
+
+        assert_eq!(loaded.get_metadata("version"), Some("1.2.0"));
+        assert_eq!(loaded.get_metadata("features"), Some("height,width,depth,weight"));
+        assert_eq!(loaded.get_metadata("trained"), None);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_linear_fast() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_linear_prob.libsvm"))?;
+
+        svm.enable_linear_fast()?;
+
+        let loaded: DenseSVM = bincode::deserialize(&bincode::serialize(&svm).unwrap()).unwrap();
+
+        assert!(loaded.classes.iter().all(|class| class.linear_weights.is_some()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn boundary_crossing() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+
+        // Along the first axis the decision value is `0.25527 - 1.2974607 * t`.
+        let expected = 0.25527 / 1.2974607;
+        let origin = [0.0, 0.0, 0.0, 0.0];
+        let direction = [1.0, 0.0, 0.0, 0.0];
+
+        let t = svm.boundary_crossing(&origin, &direction, (0, 1), (0.0, 1.0), 1e-6)?.unwrap();
+
+        assert!((t - expected).abs() < 1e-5);
+        assert_eq!(svm.boundary_crossing(&origin, &direction, (0, 1), (0.5, 1.0), 1e-6)?, None);
+        assert!(svm.boundary_crossing(&origin, &direction[.. 2], (0, 1), (0.0, 1.0), 1e-6).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn probability_warm_start() -> Result<(), Error> {
+        let model = include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm");
+        let cold = DenseSVM::try_from(model)?;
+        let mut warm = DenseSVM::try_from(model)?;
+
+        warm.set_probability_warm_start(true);
+
+        let mut problem_cold = Problem::from(&cold);
+        let mut problem_warm = Problem::from(&warm);
+        let (mut iterations_cold, mut iterations_warm) = (0, 0);
+
+        // A stream of slowly drifting, similar queries.
+        for step in 0 .. 50 {
+            for i in 0 .. cold.attributes() {
+                let x = 0.5 + step as f32 * 0.001 + i as f32 * 0.01;
+                problem_cold.features()[i] = x;
+                problem_warm.features()[i] = x;
+            }
+
+            cold.predict_probability(&mut problem_cold)?;
+            warm.predict_probability(&mut problem_warm)?;
+
+            iterations_cold += problem_cold.probability_iterations();
+            iterations_warm += problem_warm.probability_iterations();
+
+            assert_eq!(problem_cold.solution(), problem_warm.solution());
+
+            for (a, b) in problem_cold.probabilities().iter().zip(problem_warm.probabilities()) {
+                assert!((a - b).abs() < 0.01);
+            }
+        }
+
+        assert!(iterations_warm < iterations_cold);
+
+        Ok(())
+    }
+
+    #[test]
+    fn label_index_maps() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"))?;
+
+        let label_to_index = svm.label_index_map();
+        let index_to_label = svm.index_label_map();
+
+        assert_eq!(label_to_index.len(), svm.classes());
+        assert_eq!(index_to_label.len(), svm.classes());
+
+        for index in 0 .. svm.classes() {
+            let label = svm.class_label_for_index(index).unwrap();
+
+            assert_eq!(index_to_label[&index], label);
+            assert_eq!(label_to_index[&label], index);
+            assert_eq!(svm.class_index_for_label(label), Some(index));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn one_based_indices() -> Result<(), Error> {
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0\nlabel 0 1\nnr_sv 1 1\nSV\n1 1:0.5 2:-0.5\n-1 1:-0.5 2:0.5\n";
+
+        // By default attribute 1 is feature 0.
+        let positional = DenseSVM::try_from(model)?;
+        let mut problem = Problem::from(&positional);
+
+        assert_eq!(positional.attributes(), 2);
+
+        problem.features()[0] = 1.0;
+        positional.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(0));
+        assert_eq!(problem.decision_value(0, 1), Some(1.0));
+
+        // Stored by index, feature 0 is unused.
+        let indexed = DenseSVM::try_from_indexed(model)?;
+        let mut problem = Problem::from(&indexed);
+
+        assert_eq!(indexed.attributes(), 3);
+
+        problem.features()[0] = 1.0;
+        indexed.predict_value(&mut problem)?;
+        assert_eq!(problem.decision_value(0, 1), Some(0.0));
+
+        problem.features()[0] = 0.0;
+        problem.features()[1] = 1.0;
+        indexed.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(0));
+        assert_eq!(problem.decision_value(0, 1), Some(1.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn negative_labels() -> Result<(), Error> {
+        let model = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0\nlabel -1 1\nnr_sv 1 1\nSV\n1 0:1\n-1 0:-1\n";
+        let svm = DenseSVM::try_from(model)?;
+        let mut problem = Problem::from(&svm);
+
+        assert_eq!(svm.class_index_for_label(-1), Some(0));
+        assert_eq!(svm.class_label_for_index(1), Some(1));
+
+        problem.features()[0] = 1.0;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(-1));
+
+        problem.features()[0] = -1.0;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn class_frequency_correction() -> Result<(), Error> {
+        let mut svm = DenseSVM::try_from(SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        // Borderline sample slightly on the side of label 42.
+        problem.features().as_slice_mut().clone_from_slice(&[0.23122, 0.0, 0.0, 0.0]);
+
+        svm.apply_class_frequency_correction(&[5, 5])?;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(42));
+
+        // Label 21 was rare during training, so it should now win.
+        svm.apply_class_frequency_correction(&[1, 9])?;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(21));
+
+        assert!(svm.apply_class_frequency_correction(&[1, 2, 3]).is_err());
+
+        // The libSVM format can't hold the correction, so reloading drops it.
+        let reloaded = DenseSVM::try_from(svm.to_string().as_str())?;
+
+        reloaded.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(42));
+        assert!(reloaded.classes.iter().all(|class| class.correction == 1.0));
+
+        Ok(())
+    }
+
+    /// The pairwise coupling from libSVM as it was implemented before the divisions were hoisted
+    /// out of the inner loop, returning `None` if it doesn't converge.
+    fn reference_multiclass_probabilities(pairwise: &[Vec<f64>]) -> Option<Vec<f64>> {
+        let k = pairwise.len();
+        let max_iter = 100.max(k);
+        let eps = 0.005 / k as f64;
+        let mut p = vec![1.0 / k as f64; k];
+        let mut q = vec![vec![0.0; k]; k];
+        let mut qp = vec![0.0; k];
+
+        for t in 0 .. k {
+            for j in 0 .. t {
+                q[t][t] += pairwise[j][t] * pairwise[j][t];
+                q[t][j] = q[j][t];
+            }
+
+            for j in t + 1 .. k {
+                q[t][t] += pairwise[j][t] * pairwise[j][t];
+                q[t][j] = -pairwise[j][t] * pairwise[t][j];
+            }
+        }
+
+        for i in 0 ..= max_iter {
+            let mut pqp = 0.0;
+
+            for t in 0 .. k {
+                qp[t] = (0 .. k).map(|j| q[t][j] * p[j]).sum();
+                pqp += p[t] * qp[t];
+            }
+
+            let max_error = qp.iter().map(|x| (x - pqp).abs()).fold(0.0, f64::max);
+
+            if max_error < eps {
+                return Some(p);
+            }
+
+            if i == max_iter {
+                return None;
+            }
+
+            for t in 0 .. k {
+                let diff = (-qp[t] + pqp) / q[t][t];
+
+                p[t] += diff;
+                pqp = (pqp + diff * (diff * q[t][t] + 2.0 * qp[t])) / (1.0 + diff) / (1.0 + diff);
+
+                for j in 0 .. k {
+                    qp[j] = (qp[j] + diff * q[t][j]) / (1.0 + diff);
+                    p[j] /= 1.0 + diff;
+                }
+            }
+        }
+
+        None
+    }
+
+    #[test]
+    fn probabilities_match_reference_coupling() -> Result<(), Error> {
+        let models = [
+            include_str!("../../../tests/data_dense/m_csvm_linear_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_csvm_poly_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_csvm_rbf_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_csvm_sigmoid_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_nusvm_linear_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_nusvm_poly_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_nusvm_rbf_prob.libsvm"),
+            include_str!("../../../tests/data_dense/m_nusvm_sigmoid_prob.libsvm"),
+        ];
+
+        for model in models.iter() {
+            let svm = DenseSVM::try_from(*model)?;
+            let mut problem = Problem::from(&svm);
+            let k = svm.classes();
+
+            // Walk along the diagonal through all class regions.
+            for step in 0 .. 50 {
+                for i in 0 .. svm.attributes() {
+                    problem.features()[i] = step as f32 / 25.0;
+                }
+
+                let result = svm.predict_probability(&mut problem);
+                let flat = problem.pairwise.flat();
+                let pairwise = (0 .. k).map(|i| (0 .. k).map(|j| flat[(i, j)]).collect()).collect::<Vec<Vec<f64>>>();
+
+                match (result, reference_multiclass_probabilities(&pairwise)) {
+                    (Ok(()), Some(expected)) => {
+                        for (actual, expected) in problem.probabilities().iter().zip(&expected) {
+                            assert!((actual - expected).abs() < 1e-10, "{} != {}", actual, expected);
+                        }
+                    }
+                    (Err(Error::IterationsExceeded { .. }), None) => {}
+                    (result, expected) => panic!("Got {:?}, but reference returned {:?}", result, expected),
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
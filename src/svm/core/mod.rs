@@ -1,10 +1,33 @@
-use std::marker::PhantomData;
+use std::{collections::HashMap, fmt, marker::PhantomData};
 
 use crate::{
-    svm::{class::Class, Probabilities, SVMType},
+    errors::Error,
+    parser::Header,
+    svm::{class::Class, kernel::KernelInfo, KernelPrecision, PrecisionMode, Probabilities, ProbabilitySettings, SVMType},
     vectors::Triangular,
 };
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Everything a [SVMCore] holds besides the libSVM model itself, so conversions and serialization
+/// can carry it over to the new SVM.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+crate struct Settings {
+    crate precision: PrecisionMode,
+    crate kernel_precision: KernelPrecision,
+    crate probability_warm_start: bool,
+    crate probability_settings: ProbabilitySettings,
+    crate parallel_kernels: bool,
+    crate linear_fast: bool,
+
+    /// Class frequency correction per class index, see [SVMCore::apply_class_frequency_correction].
+    crate corrections: Vec<f64>,
+
+    crate metadata: HashMap<String, String>,
+}
+
 /// Generic support vector machine core, used by [DenseSVM] and [SparseSVM].
 ///
 /// The SVM holds a kernel, class information and all other numerical data read from
@@ -39,12 +62,33 @@ where
 
     crate svm_type: SVMType,
 
+    /// Precision used when computing decision values
+    crate precision: PrecisionMode,
+
+    /// Precision used when computing kernel values
+    crate kernel_precision: KernelPrecision,
+
+    /// If the probability solver should start from the previous probabilities of a [Problem]
+    crate probability_warm_start: bool,
+
+    /// Iteration limit and convergence threshold of the probability solver
+    crate probability_settings: ProbabilitySettings,
+
+    /// If kernel values of the classes should be computed on several threads (`parallel` feature only)
+    crate parallel_kernels: bool,
+
     /// SVM specific data needed for classification
     crate kernel: Box<K>,
 
+    /// Kernel family and parameters, needed for writing the model back
+    crate kernel_info: KernelInfo,
+
     /// All classes
     crate classes: Vec<Class<M32>>,
 
+    /// User supplied key-value pairs, not used for prediction
+    crate metadata: HashMap<String, String>,
+
     phantom_v32: PhantomData<V32>,
 
     phantom_v64: PhantomData<V64>,
@@ -55,6 +99,12 @@ where
     K: ?Sized,
 {
     /// Returns number of attributes, reflecting the libSVM model.
+    ///
+    /// This is the exact number of features callers must provide per [Problem], i.e., the
+    /// number of attributes of the widest support vector (for [SparseSVM]s and models loaded via
+    /// [DenseSVM::try_from_indexed] the highest attribute index plus one). Internally support vectors and
+    /// features are padded to a multiple of the SIMD width, but that padding is always zero,
+    /// never exposed and not included here.
     pub fn attributes(&self) -> usize {
         self.num_attributes
     }
@@ -63,10 +113,584 @@ where
     pub fn classes(&self) -> usize {
         self.classes.len()
     }
+
+    /// Returns the number of support vectors of class index `index`, or `None` if `index` is not
+    /// a valid class index. Regression and one-class models have a single class holding all
+    /// support vectors.
+    pub fn support_vectors_for_class(&self, index: usize) -> Option<usize> {
+        self.classes.get(index).map(|class| class.num_support_vectors)
+    }
+
+    /// Returns the total number of support vectors over all classes, reflecting the libSVM model.
+    pub fn total_support_vectors(&self) -> usize {
+        self.num_total_sv
+    }
+
+    /// Returns all class labels in class index order, i.e., the order of [Problem::probabilities].
+    pub fn labels(&self) -> impl Iterator<Item = i32> + '_ { self.classes.iter().map(|class| class.label) }
+
+    /// Returns a map from every class label to its class index.
+    ///
+    /// Build this once when many lookups are needed, instead of calling
+    /// [SVMCore::class_index_for_label] repeatedly.
+    pub fn label_index_map(&self) -> HashMap<i32, usize> {
+        self.classes.iter().enumerate().map(|(i, class)| (class.label, i)).collect()
+    }
+
+    /// Returns a map from every class index to its class label, the inverse of [SVMCore::label_index_map].
+    pub fn index_label_map(&self) -> HashMap<usize, i32> {
+        self.classes.iter().enumerate().map(|(i, class)| (i, class.label)).collect()
+    }
+
+    /// Returns the kernel family and its parameters, e.g., for logging.
+    pub fn kernel_info(&self) -> KernelInfo {
+        self.kernel_info
+    }
+
+    /// Returns `true` if the model was trained with probability estimates (`svm-train -b 1`).
+    ///
+    /// Only then [Predict::predict_probability] can be used for classification.
+    ///
+    /// ```
+    /// #![feature(try_from)]
+    ///
+    /// use ffsvm::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let plain = DenseSVM::try_from(SAMPLE_MODEL)?;
+    ///     assert!(!plain.has_probabilities());
+    ///
+    ///     let with_probabilities = SAMPLE_MODEL.replace("nr_sv", "probA -3.09609\nprobB -0.368652\nnr_sv");
+    ///     let svm = DenseSVM::try_from(with_probabilities.as_str())?;
+    ///     assert!(svm.has_probabilities());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn has_probabilities(&self) -> bool {
+        self.probabilities.is_some()
+    }
+
+    /// Overrides the bias `rho` of the decision function between class index `i` and `j`.
+    ///
+    /// # Description
+    ///
+    /// The decision value between two classes is computed as `sum - rho`, where positive values
+    /// vote for class `i` and negative for class `j`. Changing `rho` therefore moves the operating
+    /// point of that particular class pair without touching any other, e.g., to calibrate
+    /// thresholds of a multiclass model.
+    ///
+    /// For regression models the only valid pair is `(0, 1)`.
+    ///
+    /// # Returns
+    ///
+    /// [Error::InvalidClassPair] unless `i < j` and both are valid indices.
+    pub fn set_rho(&mut self, i: usize, j: usize, value: f64) -> Result<(), Error> {
+        if i >= j || j >= self.rho.dimension {
+            return Err(Error::InvalidClassPair { i, j });
+        }
+
+        self.rho[(i, j)] = value;
+
+        Ok(())
+    }
+
+    /// Sets the [PrecisionMode] used for computing decision values.
+    pub fn set_precision_mode(&mut self, precision: PrecisionMode) {
+        self.precision = precision;
+    }
+
+    /// Sets the [KernelPrecision] used for computing kernel values.
+    pub fn set_kernel_precision(&mut self, kernel_precision: KernelPrecision) {
+        self.kernel_precision = kernel_precision;
+    }
+
+    /// Checks feature rows before a batch prediction.
+    ///
+    /// # Description
+    ///
+    /// Each row must have exactly [SVMCore::attributes] values, all of them finite. Instead of
+    /// failing on the first bad row, all problems are collected so callers can filter their input
+    /// before calling [Predict::predict_batch].
+    ///
+    /// # Returns
+    ///
+    /// The index of every bad row together with [Error::DimensionMismatch] or
+    /// [Error::NonFiniteFeature] (for the first non-finite value). Empty if all rows are valid.
+    pub fn validate_inputs(&self, rows: &[Vec<f32>]) -> Vec<(usize, Error)> {
+        let mut errors = Vec::new();
+
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != self.num_attributes {
+                errors.push((
+                    i,
+                    Error::DimensionMismatch {
+                        expected: self.num_attributes,
+                        actual: row.len(),
+                    },
+                ));
+            } else if let Some(index) = row.iter().position(|x| !x.is_finite()) {
+                errors.push((i, Error::NonFiniteFeature { index, value: row[index] }));
+            }
+        }
+
+        errors
+    }
+
+    /// Attaches a metadata entry (e.g., model version, training date or feature names) to this
+    /// SVM, replacing any previous value for `key`. Metadata is never used for prediction.
+    ///
+    /// Metadata is kept when serializing a [DenseSVM] with the `serde` feature, but can't be
+    /// stored in libSVM's text format.
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_string(), value.to_string());
+    }
+
+    /// Returns the metadata entry previously stored for `key`, if any.
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Lets the multiclass probability solver start from the probabilities a [Problem] already holds.
+    ///
+    /// # Description
+    ///
+    /// By default [Predict::predict_probability] starts from a uniform distribution. When the
+    /// same [Problem] is reused for a stream of similar inputs, starting from the previous
+    /// result usually converges in fewer iterations (see [Problem::probability_iterations]).
+    /// Previous probabilities are only used if they form a valid distribution.
+    ///
+    /// The solver stops once its error falls below the same threshold either way, so results
+    /// match the cold start result within that tolerance, but are not bit-identical.
+    pub fn set_probability_warm_start(&mut self, warm_start: bool) {
+        self.probability_warm_start = warm_start;
+    }
+
+    /// Sets the iteration limit and convergence threshold of the multiclass probability solver,
+    /// see [ProbabilitySettings].
+    pub fn set_probability_settings(&mut self, settings: ProbabilitySettings) {
+        self.probability_settings = settings;
+    }
+
+    /// Returns the current [ProbabilitySettings].
+    pub fn probability_settings(&self) -> ProbabilitySettings {
+        self.probability_settings
+    }
+
+    /// Computes the kernel values of each class on its own thread when predicting a single [Problem].
+    ///
+    /// # Description
+    ///
+    /// This only pays off for models with many support vectors per class, for small models the
+    /// thread pool overhead outweighs the gain, which is why it is off by default. Results are
+    /// identical to the serial computation. Don't combine it with [Predict::predict_batch_parallel],
+    /// which already keeps all cores busy.
+    ///
+    /// The first prediction of each [Problem] in this mode allocates a scratch buffer. Only
+    /// available with the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel_kernels(&mut self, parallel: bool) {
+        self.parallel_kernels = parallel;
+    }
+
+    /// Rescales each class' decision contributions by its inverse training frequency.
+    ///
+    /// # Description
+    ///
+    /// Models trained on imbalanced data can favor the majority class. Given the number of
+    /// training samples per class (in class index order), this computes for each class `c`
+    /// the factor
+    ///
+    /// ```ignore
+    /// w_c = sum(counts) / (num_classes * counts[c])
+    /// ```
+    ///
+    /// and scales the part of each pairwise decision value contributed by the support vectors
+    /// of class `c` with `w_c`. The correction is optional; uniform counts yield `w_c = 1`
+    /// and reproduce the original model.
+    ///
+    /// libSVM's model format has no place for the correction, so it is dropped when the SVM is
+    /// written via `Display` and has to be applied again after loading. Serializing with serde
+    /// keeps it.
+    ///
+    /// # Returns
+    ///
+    /// [Error::DimensionMismatch] if `counts` does not have one entry per class.
+    pub fn apply_class_frequency_correction(&mut self, counts: &[usize]) -> Result<(), Error> {
+        if counts.len() != self.classes.len() {
+            return Err(Error::DimensionMismatch {
+                expected: self.classes.len(),
+                actual: counts.len(),
+            });
+        }
+
+        let total = counts.iter().sum::<usize>() as f64;
+        let num_classes = self.classes.len() as f64;
+
+        for (class, count) in self.classes.iter_mut().zip(counts) {
+            class.correction = total / (num_classes * (*count).max(1) as f64);
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, M32, V32, V64> SVMCore<K, M32, V32, V64>
+where
+    K: ?Sized,
+{
+    /// Returns the libSVM model header describing this SVM, e.g., to turn it back into a [ModelFile].
+    ///
+    /// Returns [Error::CustomKernel] for custom kernels, which a header can't describe.
+    crate fn to_header(&self) -> Result<Header<'static>, Error> {
+        if self.kernel_info == KernelInfo::Custom {
+            return Err(Error::CustomKernel);
+        }
+
+        let is_classifier = self.svm_type.is_classifier();
+        let (gamma, coef0, degree) = self.kernel_info.parameters();
+
+        let (prob_a, prob_b) = match &self.probabilities {
+            // SVRs only have `probA`.
+            Some(probabilities) if probabilities.b.data.is_empty() => (Some(probabilities.a.data.clone()), None),
+            Some(probabilities) => (Some(probabilities.a.data.clone()), Some(probabilities.b.data.clone())),
+            None => (None, None),
+        };
+
+        Ok(Header {
+            svm_type: self.svm_type.to_string().into(),
+            kernel_type: self.kernel_info.to_string().into(),
+            gamma,
+            coef0,
+            degree,
+            nr_class: if is_classifier { self.classes.len() as u32 } else { 2 },
+            total_sv: self.num_total_sv as u32,
+            rho: self.rho.data.clone(),
+            label: if is_classifier { self.classes.iter().map(|c| c.label).collect() } else { Vec::new() },
+            prob_a,
+            prob_b,
+            nr_sv: if is_classifier { self.classes.iter().map(|c| c.num_support_vectors as u32).collect() } else { Vec::new() },
+        })
+    }
+
+    /// Writes the libSVM model header, up to and including the `SV` line.
+    ///
+    /// Fails for custom kernels, which a header can't describe.
+    crate fn write_header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kernel_info == KernelInfo::Custom {
+            return Err(fmt::Error);
+        }
+
+        let is_classifier = self.svm_type.is_classifier();
+
+        writeln!(f, "svm_type {}", self.svm_type)?;
+
+        writeln!(f, "kernel_type {}", self.kernel_info)?;
+
+        let (gamma, coef0, degree) = self.kernel_info.parameters();
+
+        if let Some(degree) = degree {
+            writeln!(f, "degree {}", degree)?;
+        }
+
+        if let Some(gamma) = gamma {
+            writeln!(f, "gamma {}", gamma)?;
+        }
+
+        if let Some(coef0) = coef0 {
+            writeln!(f, "coef0 {}", coef0)?;
+        }
+
+        // libSVM writes `nr_class 2` for regression and one-class models.
+        writeln!(f, "nr_class {}", if is_classifier { self.classes.len() } else { 2 })?;
+        writeln!(f, "total_sv {}", self.num_total_sv)?;
+        write_list(f, "rho", &self.rho.data)?;
+
+        if is_classifier {
+            write_list(f, "label", &self.classes.iter().map(|c| c.label).collect::<Vec<_>>())?;
+        }
+
+        if let Some(probabilities) = &self.probabilities {
+            write_list(f, "probA", &probabilities.a.data)?;
+
+            // SVRs only have `probA`.
+            if !probabilities.b.data.is_empty() {
+                write_list(f, "probB", &probabilities.b.data)?;
+            }
+        }
+
+        if is_classifier {
+            write_list(f, "nr_sv", &self.classes.iter().map(|c| c.num_support_vectors).collect::<Vec<_>>())?;
+        }
+
+        writeln!(f, "SV")
+    }
+}
+
+/// Writes a header line `name` followed by all `values`.
+fn write_list<T>(f: &mut fmt::Formatter<'_>, name: &str, values: &[T]) -> fmt::Result
+where
+    T: fmt::Display,
+{
+    write!(f, "{}", name)?;
+
+    for value in values {
+        write!(f, " {}", value)?;
+    }
+
+    writeln!(f)
 }
 
 macro_rules! impl_common_svm {
     ($v32:ty) => {
+        /// Loads a libSVM model from the file at `path`.
+        ///
+        /// # Returns
+        ///
+        /// The SVM, [Error::Io] if the file could not be read, or any error [TryFrom] would
+        /// return for the file's contents.
+        pub fn from_file<P>(path: P) -> Result<Self, Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            let model = std::fs::read_to_string(path)?;
+            Self::try_from(model.as_str())
+        }
+
+        /// Loads a libSVM model by memory mapping the file at `path`.
+        ///
+        /// # Description
+        ///
+        /// Unlike [Self::from_file] the file is not copied into a `String` first, but parsed
+        /// directly from the mapped pages, so loading large models doesn't temporarily need twice
+        /// their size in memory. The SVM still owns all its numeric data once this returns.
+        ///
+        /// The file must not be modified while loading. Only available with the `mmap` feature.
+        ///
+        /// # Returns
+        ///
+        /// The SVM, [Error::Io] if the file could not be mapped, [Error::ParsingError] if it is not
+        /// valid UTF-8, or any error [TryFrom] would return for the file's contents.
+        #[cfg(feature = "mmap")]
+        pub fn from_mmap<P>(path: P) -> Result<Self, Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            let file = std::fs::File::open(path)?;
+
+            // Safe as long as nobody modifies the file while we parse it, see above.
+            let mmap = unsafe { memmap::Mmap::map(&file)? };
+            let model = std::str::from_utf8(&mmap).map_err(|e| Error::ParsingError(format!("{}", e)))?;
+
+            Self::try_from(model)
+        }
+
+        /// Reads a libSVM model from any [std::io::Read] source, e.g., an archive or network stream.
+        ///
+        /// # Returns
+        ///
+        /// The SVM, [Error::Io] if reading failed, or any error [TryFrom] would return for the
+        /// model's contents.
+        pub fn from_reader<R>(mut reader: R) -> Result<Self, Error>
+        where
+            R: std::io::Read,
+        {
+            let mut model = String::new();
+            reader.read_to_string(&mut model)?;
+
+            Self::try_from(model.as_str())
+        }
+
+        /// Loads a libSVM model embedded into the executable, e.g., via `include_bytes!("model.txt")`.
+        ///
+        /// # Description
+        ///
+        /// This allows shipping a single binary without any file IO at runtime. The model text is
+        /// parsed directly from the embedded bytes without copying it first. Parsing itself still
+        /// happens when this is called, since the support vectors need to be copied into
+        /// SIMD-aligned storage anyway. With the `binary` feature, [DenseSVM::from_bincode]
+        /// loads a compact binary form instead, which skips parsing the text.
+        ///
+        /// # Returns
+        ///
+        /// The SVM, [Error::ParsingError] if the bytes are not valid UTF-8, or any error [TryFrom]
+        /// would return for the model's contents.
+        pub fn from_static_bytes(bytes: &'static [u8]) -> Result<Self, Error> {
+            let model = std::str::from_utf8(bytes).map_err(|e| Error::ParsingError(format!("{}", e)))?;
+
+            Self::try_from(model)
+        }
+
+        /// Writes this SVM in libSVM's model format, like its [std::fmt::Display] implementation.
+        ///
+        /// # Returns
+        ///
+        /// The model, or [Error::CustomKernel] for custom kernels, which libSVM's format can't
+        /// describe (printing those fails instead).
+        pub fn to_model_string(&self) -> Result<String, Error> {
+            if self.kernel_info == KernelInfo::Custom {
+                return Err(Error::CustomKernel);
+            }
+
+            Ok(self.to_string())
+        }
+
+        /// Converts this SVM back into the [ModelFile] it could have been loaded from, writing
+        /// all attributes of every support vector. Settings not part of libSVM's format are
+        /// available from [SVMCore::settings].
+        ///
+        /// Returns [Error::CustomKernel] for custom kernels.
+        crate fn to_model_file(&self) -> Result<ModelFile<'static>, Error> {
+            // Classifiers have one coefficient per other class, regression and one-class models one.
+            let num_coefficients = (self.classes.len() - 1).max(1);
+            let mut vectors = Vec::with_capacity(self.num_total_sv);
+
+            for class in &self.classes {
+                let coefficients = class.coefficients.flat();
+
+                for i_vector in 0 .. class.num_support_vectors {
+                    vectors.push(SupportVector {
+                        coefs: (0 .. num_coefficients).map(|i_coefficient| coefficients[(i_coefficient, i_vector)]).collect(),
+                        features: class.dense_attributes(i_vector, self.num_attributes),
+                    });
+                }
+            }
+
+            Ok(ModelFile {
+                header: self.to_header()?,
+                vectors,
+            })
+        }
+
+        /// Returns all settings of this SVM that are not part of its libSVM model.
+        crate fn settings(&self) -> Settings {
+            Settings {
+                precision: self.precision,
+                kernel_precision: self.kernel_precision,
+                probability_warm_start: self.probability_warm_start,
+                probability_settings: self.probability_settings,
+                parallel_kernels: self.parallel_kernels,
+                linear_fast: self.classes.iter().all(|class| class.linear_weights.is_some()),
+                corrections: self.classes.iter().map(|class| class.correction).collect(),
+                metadata: self.metadata.clone(),
+            }
+        }
+
+        /// Applies settings obtained from [SVMCore::settings] of an SVM with the same classes.
+        ///
+        /// Returns [Error::DimensionMismatch] if the number of classes differs.
+        crate fn apply_settings(&mut self, settings: Settings) -> Result<(), Error> {
+            if settings.corrections.len() != self.classes.len() {
+                return Err(Error::DimensionMismatch {
+                    expected: self.classes.len(),
+                    actual: settings.corrections.len(),
+                });
+            }
+
+            for (class, correction) in self.classes.iter_mut().zip(&settings.corrections) {
+                class.correction = *correction;
+            }
+
+            self.precision = settings.precision;
+            self.kernel_precision = settings.kernel_precision;
+            self.probability_warm_start = settings.probability_warm_start;
+            self.probability_settings = settings.probability_settings;
+            self.parallel_kernels = settings.parallel_kernels;
+            self.metadata = settings.metadata;
+
+            if settings.linear_fast {
+                self.enable_linear_fast()?;
+            }
+
+            Ok(())
+        }
+
+        /// Switches a linear classifier to a faster prediction mode.
+        ///
+        /// # Description
+        ///
+        /// With a linear kernel each decision function `sum(coef * <sv, x>) - rho` can be
+        /// rewritten as `<w, x> - rho`, where `w` only depends on the model. This computes all
+        /// `w` once, so predictions afterwards take a single dot product per class pair (and
+        /// side) instead of evaluating the kernel against every support vector.
+        ///
+        /// Weights and dot products are always computed in `f64`, dense SVMs use the `f64`
+        /// support vectors of the model file, so this mode behaves like [KernelPrecision::Double]
+        /// whatever the setting. Decision values are identical to the regular path whenever its
+        /// arithmetic is exact. Otherwise they differ by the `f32` rounding of the regular path
+        /// with [KernelPrecision::Single], which can flip the labels of samples very close to
+        /// the decision boundary.
+        ///
+        /// Kernel values are not computed anymore in this mode, so methods inspecting them
+        /// (e.g., [SVMCore::active_support_vectors]) should not be used afterwards, and the
+        /// [PrecisionMode] is ignored.
+        ///
+        /// # Returns
+        ///
+        /// [Error::NotLinear] for non-linear kernels, or [Error::NotClassifier] for regression
+        /// and one-class models.
+        pub fn enable_linear_fast(&mut self) -> Result<(), Error> {
+            if !self.svm_type.is_classifier() {
+                return Err(Error::NotClassifier);
+            }
+
+            if self.kernel_info != KernelInfo::Linear {
+                return Err(Error::NotLinear);
+            }
+
+            let num_rows = self.classes.len() - 1;
+            let num_attributes = self.num_attributes;
+
+            for class in self.classes.iter_mut() {
+                let coefficients = class.coefficients.flat();
+                let mut weights = vec![vec![0.0; num_attributes]; num_rows];
+
+                // One weight vector per coefficient row, summing all support vectors weighted by their coefficient.
+                for i_vector in 0 .. class.num_support_vectors {
+                    let attributes = class.dense_attributes(i_vector, num_attributes);
+
+                    for (row, weight) in weights.iter_mut().enumerate() {
+                        let coefficient = coefficients[(row, i_vector)];
+
+                        for (w, attribute) in weight.iter_mut().zip(&attributes) {
+                            *w += coefficient * attribute.value;
+                        }
+                    }
+                }
+
+                class.linear_weights = Some(weights);
+            }
+
+            Ok(())
+        }
+
+        /// Overrides `gamma` of an RBF kernel.
+        ///
+        /// # Description
+        ///
+        /// Useful for probing how sensitive a model is to `gamma` without retraining, or for
+        /// restoring a `gamma` that was written with too few digits. Support vectors and
+        /// coefficients are kept, so this changes what the model predicts. To only compare
+        /// decision values for a few gammas, [DenseSVM::decision_values_for_gammas] is cheaper.
+        ///
+        /// # Returns
+        ///
+        /// [Error::NotRbf] for models not using an RBF kernel.
+        pub fn set_rbf_gamma(&mut self, gamma: f64) -> Result<(), Error> {
+            match self.kernel_info {
+                KernelInfo::Rbf { .. } => {}
+                _ => return Err(Error::NotRbf),
+            }
+
+            let kernel = Rbf { gamma };
+
+            self.kernel = Box::new(kernel);
+            self.kernel_info = KernelInfo::from(kernel);
+
+            Ok(())
+        }
+
         /// Finds the class index for a given label.
         ///
         /// # Description
@@ -81,7 +705,7 @@ macro_rules! impl_common_svm {
         /// If the label was found its index returned in the [Option]. Otherwise `None`
         /// is returned.
         ///
-        pub fn class_index_for_label(&self, label: u32) -> Option<usize> {
+        pub fn class_index_for_label(&self, label: i32) -> Option<usize> {
             for (i, class) in self.classes.iter().enumerate() {
                 if class.label != label {
                     continue;
@@ -105,7 +729,7 @@ macro_rules! impl_common_svm {
         ///
         /// If the index was found it is returned in the [Option]. Otherwise `None`
         /// is returned.
-        pub fn class_label_for_index(&self, index: usize) -> Option<u32> {
+        pub fn class_label_for_index(&self, index: usize) -> Option<i32> {
             if index >= self.classes.len() {
                 None
             } else {
@@ -113,17 +737,394 @@ macro_rules! impl_common_svm {
             }
         }
 
+        /// Predicts the label of a [Problem] and returns it together with all pairwise votes.
+        ///
+        /// # Description
+        ///
+        /// The returned vector is a copy of the votes each class received, in class index
+        /// order (see [SVMCore::class_label_for_index]). For `n` classes the votes always
+        /// sum up to `n * (n - 1) / 2`. This can be useful to combine the outputs of multiple
+        /// SVMs.
+        ///
+        /// # Returns
+        ///
+        /// The winning label and all votes, or [Error::NotClassifier] for regression models.
+        pub fn predict_votes(&self, problem: &mut Problem<$v32>) -> Result<(i32, Vec<u32>), Error> {
+            self.predict_value(problem)?;
+
+            match problem.result {
+                Solution::Label(label) => Ok((label, problem.vote.clone())),
+                _ => Err(Error::NotClassifier),
+            }
+        }
+
+        /// Predicts the label of a [Problem] and returns it together with a confidence in `[0, 1]`.
+        ///
+        /// # Description
+        ///
+        /// If the model was trained with probability estimates the confidence is the estimated
+        /// probability of the winning class. Otherwise it is the fraction of pairwise decisions
+        /// the winning class won. The latter is much coarser, but always available.
+        ///
+        /// Confidences can be fed into a [ConfidenceSketch] to tune rejection thresholds.
+        ///
+        /// # Returns
+        ///
+        /// The winning label and its confidence, or [Error::NotClassifier] for regression models.
+        pub fn predict_with_confidence(&self, problem: &mut Problem<$v32>) -> Result<(i32, f64), Error> {
+            if !self.svm_type.is_classifier() {
+                return Err(Error::NotClassifier);
+            }
+
+            if self.probabilities.is_some() {
+                self.predict_probability(problem)?;
+
+                let index = find_max_index(problem.probabilities.flat());
+                Ok((self.classes[index].label, problem.probabilities.flat()[index]))
+            } else {
+                self.predict_value(problem)?;
+
+                let index = find_max_index(&problem.vote);
+                let comparisons = (self.classes.len() - 1).max(1) as f64;
+                Ok((self.classes[index].label, f64::from(problem.vote[index]) / comparisons))
+            }
+        }
+
+        /// Counts the support vectors relevant for a previously predicted [Problem].
+        ///
+        /// # Description
+        ///
+        /// Returns the number of support vectors (across all classes) whose kernel value with the
+        /// problem's features has an absolute value above `threshold`. The [Problem] must have
+        /// been predicted before, since this only inspects the kernel values computed then.
+        ///
+        /// For RBF kernels a low count means the problem is far from all training data,
+        /// and the model is extrapolating.
+        pub fn active_support_vectors(&self, problem: &Problem<$v32>, threshold: f64) -> usize {
+            let kernel_values = problem.kernel_values.flat();
+            let mut active = 0;
+
+            for (i, class) in self.classes.iter().enumerate() {
+                for j in 0 .. class.num_support_vectors {
+                    if kernel_values[(i, j)].abs() > threshold {
+                        active += 1;
+                    }
+                }
+            }
+
+            active
+        }
+
+        /// Returns the probability estimates of a [Problem] together with their class labels.
+        ///
+        /// # Description
+        ///
+        /// [Problem::probabilities] are stored in class index order. This pairs each estimate with
+        /// the label of its class and sorts the result by label. The [Problem] must have been
+        /// predicted with [Predict::predict_probability] before.
+        ///
+        /// # Returns
+        ///
+        /// One `(label, probability)` pair per class, or [Error::NoProbabilities] if the model
+        /// was trained without probability estimates.
+        pub fn labeled_probabilities(&self, problem: &Problem<$v32>) -> Result<Vec<(i32, f64)>, Error> {
+            if self.probabilities.is_none() {
+                return Err(Error::NoProbabilities);
+            }
+
+            let mut labeled = self.classes.iter().map(|class| class.label).zip(problem.probabilities().iter().cloned()).collect::<Vec<_>>();
+            labeled.sort_by_key(|(label, _)| *label);
+
+            Ok(labeled)
+        }
+
+        /// Estimates the decision value of a class pair if one support vector were removed.
+        ///
+        /// # Description
+        ///
+        /// Given a [Problem] that was predicted before, this subtracts the contribution
+        /// `coefficient * kernel_value` of support vector `sv_index` of class index `class` from
+        /// the cached decision value of the class pair `(i, j)`. The model itself is not changed,
+        /// which makes this a cheap leave-one-out estimate, e.g., for influence analysis.
+        ///
+        /// # Returns
+        ///
+        /// The decision value without that support vector, [Error::InvalidClassPair] unless
+        /// `i < j`, both are valid indices and `class` is one of them, or
+        /// [Error::InvalidSupportVector] if `sv_index` is not a support vector of `class`.
+        pub fn decision_without_sv(&self, problem: &Problem<$v32>, class: usize, sv_index: usize, pair: (usize, usize)) -> Result<f64, Error> {
+            let (i, j) = pair;
+
+            if i >= j || j >= self.classes.len() || (class != i && class != j) {
+                return Err(Error::InvalidClassPair { i, j });
+            }
+
+            if sv_index >= self.classes[class].num_support_vectors {
+                return Err(Error::InvalidSupportVector { class, index: sv_index });
+            }
+
+            // Class `i` stores its coefficients against `j` in row `j - 1`, class `j` against `i` in row `i`.
+            let row = if class == i { j - 1 } else { i };
+
+            let coefficient = self.classes[class].coefficients.row_as_flat(row)[sv_index];
+            let kernel_value = problem.kernel_values.row_as_flat(class)[sv_index];
+
+            Ok(problem.decision_values[(i, j)] - coefficient * kernel_value * self.classes[class].correction)
+        }
+
+        /// Predicts a label from kernel values the caller already placed into the [Problem].
+        ///
+        /// # Description
+        ///
+        /// Instead of computing kernel values from the problem's features, this method uses the
+        /// ones set via [Problem::kernel_values_mut], and only performs the decision and voting
+        /// steps. This allows computing kernel values externally, e.g., on dedicated hardware.
+        ///
+        /// # Returns
+        ///
+        /// The predicted label, [Error::DimensionMismatch] if the problem was not created for a
+        /// model of the same shape or its kernel values lack a row per class or a value per
+        /// support vector, or [Error::NotClassifier] for regression and one-class models.
+        pub fn predict_from_kernel_values(&self, problem: &mut Problem<$v32>) -> Result<i32, Error> {
+            if !self.svm_type.is_classifier() {
+                return Err(Error::NotClassifier);
+            }
+
+            self.check_dimensions(problem)?;
+
+            self.compute_classification_values(problem);
+
+            let label = self.classes[find_max_index(&problem.vote)].label;
+            problem.result = Solution::Label(label);
+            problem.label_source = Some(LabelSource::Votes);
+
+            Ok(label)
+        }
+
+        /// Predicts a [Problem] and returns a heuristic probability distribution over all classes.
+        ///
+        /// # Description
+        ///
+        /// Each class is scored with the number of pairwise votes it won during prediction, and
+        /// the scores are normalized with a softmax. The most likely class is therefore always
+        /// the predicted label, with classes having the same number of votes sharing the same
+        /// value (ties resolve to the lower class index, like the prediction). Unlike
+        /// [Predict::predict_probability] this works for models trained without probability
+        /// estimates.
+        ///
+        /// **Note**: These values are **not** calibrated like libSVM's Platt scaling. They sum up to
+        /// `1.0` and are ordered sensibly, but should not be interpreted as actual likelihoods.
+        ///
+        /// # Returns
+        ///
+        /// One value per class in class index order, or [Error::NotClassifier] for regression and
+        /// one-class models.
+        pub fn predict_pseudo_probability(&self, problem: &mut Problem<$v32>) -> Result<Vec<f64>, Error> {
+            if !self.svm_type.is_classifier() {
+                return Err(Error::NotClassifier);
+            }
+
+            self.predict_value(problem)?;
+
+            let mut scores = problem.vote.iter().map(|vote| f64::from(*vote)).collect::<Vec<_>>();
+
+            // Subtract the maximum for numerical stability of `exp()`.
+            let max = scores.iter().cloned().fold(std::f64::NEG_INFINITY, f64::max);
+            let mut total = 0.0;
+
+            for score in scores.iter_mut() {
+                *score = (*score - max).exp();
+                total += *score;
+            }
+
+            for score in scores.iter_mut() {
+                *score /= total;
+            }
+
+            Ok(scores)
+        }
+
+        /// Predicts a [Problem] with a binary classifier and returns its decision value.
+        ///
+        /// # Description
+        ///
+        /// Binary models have a single decision function. Positive values favor the class with
+        /// index `0`, negative ones the class with index `1` (see [SVMCore::class_label_for_index]),
+        /// and the predicted label flips where the value crosses `0.0`. This is the value to sweep
+        /// thresholds over, e.g., for ROC analysis.
+        ///
+        /// # Returns
+        ///
+        /// The decision value, or [Error::NotClassifier] unless this is a binary classifier.
+        pub fn decision_score(&self, problem: &mut Problem<$v32>) -> Result<f64, Error> {
+            if !self.svm_type.is_classifier() || self.classes.len() != 2 {
+                return Err(Error::NotClassifier);
+            }
+
+            self.predict_value(problem)?;
+
+            Ok(problem.decision_values[(0, 1)])
+        }
+
+        /// Checks the structural invariants of this SVM.
+        ///
+        /// # Description
+        ///
+        /// Loading only validates what is needed to parse a model. Call this right after loading
+        /// models from untrusted sources to fail fast on malformed files instead of panicking (or
+        /// silently mispredicting) later. The following must hold:
+        ///
+        /// * Every class has one coefficient row per decision function it takes part in (one
+        ///   for regression and one-class models) and one coefficient per support vector.
+        /// * Every class holds as many support vectors as it claims, and they add up to the
+        ///   total number of support vectors.
+        /// * `rho` and the probability parameters (if any) have one entry per class pair.
+        ///
+        /// # Returns
+        ///
+        /// `Ok(())`, or [Error::InconsistentModel] describing the first violated invariant.
+        pub fn verify_self_consistency(&self) -> Result<(), Error> {
+            let inconsistent = |detail: String| Err(Error::InconsistentModel(detail));
+
+            if self.classes.is_empty() {
+                return inconsistent("Model has no classes".to_owned());
+            }
+
+            let num_classes = self.classes.len().max(2);
+            let num_pairs = num_classes * (num_classes - 1) / 2;
+            let mut num_total_sv = 0;
+
+            for (i, class) in self.classes.iter().enumerate() {
+                let coefficient_rows = class.coefficients.row_iter().count();
+                let support_vectors = class.support_vectors.row_iter().count();
+
+                if coefficient_rows != num_classes - 1 {
+                    return inconsistent(format!("Class {} has {} coefficient rows, expected {}", i, coefficient_rows, num_classes - 1));
+                }
+
+                if support_vectors != class.num_support_vectors {
+                    return inconsistent(format!("Class {} has {} support vectors, expected {}", i, support_vectors, class.num_support_vectors));
+                }
+
+                if class.num_support_vectors > 0 && class.coefficients.row_as_flat(0).len() < class.num_support_vectors {
+                    return inconsistent(format!("Class {} has fewer coefficients than support vectors", i));
+                }
+
+                num_total_sv += class.num_support_vectors;
+            }
+
+            if num_total_sv != self.num_total_sv {
+                return inconsistent(format!("Classes have {} support vectors, header claims {}", num_total_sv, self.num_total_sv));
+            }
+
+            if self.rho.dimension != num_classes || self.rho.data.len() != num_pairs {
+                return inconsistent(format!("rho has {} entries, expected {}", self.rho.data.len(), num_pairs));
+            }
+
+            if let Some(probabilities) = &self.probabilities {
+                let (a, b) = (&probabilities.a, &probabilities.b);
+
+                let valid = match self.svm_type {
+                    SVMType::CSvc | SVMType::NuSvc => a.data.len() == num_pairs && b.data.len() == num_pairs,
+                    SVMType::ESvr | SVMType::NuSvr => a.data.len() == 1,
+                    SVMType::OneClass => true,
+                };
+
+                if !valid {
+                    return inconsistent(format!("Probability parameters have {} and {} entries, expected {}", a.data.len(), b.data.len(), num_pairs));
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Makes sure the [Problem] was created for a model of the same shape as this one.
+        crate fn check_dimensions(&self, problem: &Problem<$v32>) -> Result<(), Error> {
+            let checks = [
+                (self.num_attributes, problem.num_attributes),
+                (self.num_total_sv, problem.num_total_sv),
+                (self.classes.len(), problem.vote.len()),
+            ];
+
+            for &(expected, actual) in checks.iter() {
+                if expected != actual {
+                    return Err(Error::DimensionMismatch { expected, actual });
+                }
+            }
+
+            // Kernel values can be replaced via `kernel_values_mut`, so their shape is checked as well.
+            let (rows, _) = problem.kernel_values.dimension();
+
+            if rows != self.classes.len() {
+                return Err(Error::DimensionMismatch {
+                    expected: self.classes.len(),
+                    actual: rows,
+                });
+            }
+
+            for (i, class) in self.classes.iter().enumerate() {
+                let actual = problem.kernel_values.row_as_flat(i).len();
+
+                if actual < class.num_support_vectors {
+                    return Err(Error::DimensionMismatch {
+                        expected: class.num_support_vectors,
+                        actual,
+                    });
+                }
+            }
+
+            Ok(())
+        }
+
         /// Computes the kernel values for this problem
         crate fn compute_kernel_values(&self, problem: &mut Problem<$v32>) {
+            #[cfg(feature = "parallel")]
+            {
+                if self.parallel_kernels {
+                    return self.compute_kernel_values_parallel(problem);
+                }
+            }
+
             // Get current problem and decision values array
             let features = &problem.features;
             let kernel_values = &mut problem.kernel_values;
+            let scratch = &mut problem.kernel_scratch;
 
             // Compute kernel values per class
             for (i, class) in self.classes.iter().enumerate() {
                 let kvalues = kernel_values.row_as_flat_mut(i);
 
-                self.kernel.compute(&class.support_vectors, features.as_raw(), kvalues);
+                class.compute_kernel_values(&*self.kernel, self.kernel_precision, features.as_raw(), kvalues, scratch);
+            }
+        }
+
+        /// Computes the kernel values for this problem, distributing classes across threads.
+        #[cfg(feature = "parallel")]
+        crate fn compute_kernel_values_parallel(&self, problem: &mut Problem<$v32>) {
+            use rayon::prelude::*;
+
+            // `SimdMatrix` can't be split into disjoint mutable rows, so every class writes into its
+            // own chunk of a flat scratch buffer instead, which is copied over afterwards.
+            let stride = self.classes.iter().map(|class| class.num_support_vectors).max().unwrap_or(0).max(1);
+            let needed = stride * self.classes.len();
+
+            if problem.kernel_scratch.len() < needed {
+                problem.kernel_scratch.resize(needed, 0.0);
+            }
+
+            let features = &problem.features;
+            let kernel_precision = self.kernel_precision;
+
+            problem.kernel_scratch[.. needed]
+                .par_chunks_mut(stride)
+                .zip(self.classes.par_iter())
+                .for_each(|(kvalues, class)| class.compute_kernel_values(&*self.kernel, kernel_precision, features.as_raw(), kvalues, &mut Vec::new()));
+
+            for (i, class) in self.classes.iter().enumerate() {
+                let num_sv = class.num_support_vectors;
+                let start = i * stride;
+
+                problem.kernel_values.row_as_flat_mut(i)[.. num_sv].copy_from_slice(&problem.kernel_scratch[start .. start + num_sv]);
             }
         }
 
@@ -133,19 +1134,25 @@ macro_rules! impl_common_svm {
         // based on Method 2 from the paper "Probability Estimates for Multi-class
         // Classification by Pairwise Coupling", Journal of Machine Learning Research 5 (2004) 975-1005,
         // by Ting-Fan Wu, Chih-Jen Lin and Ruby C. Weng.
-        crate fn compute_multiclass_probabilities(&self, problem: &mut Problem<$v32>) -> Result<(), Error> {
+        crate fn compute_multiclass_probabilities(&self, problem: &mut Problem<$v32>, max_iter: usize) -> Result<(), Error> {
             let num_classes = self.classes.len();
-            let max_iter = 100.max(num_classes);
             let mut q = problem.q.flat_mut();
             let qp = &mut problem.qp;
-            let eps = 0.005 / num_classes as f64; // Magic number .005 comes from libSVM.
+            let eps = self.probability_settings.epsilon / num_classes as f64;
             let pairwise = problem.pairwise.flat();
             let probabilities = problem.probabilities.flat_mut();
 
+            // Previous results are only a sensible starting point if they form a distribution.
+            let warm_start = self.probability_warm_start
+                && probabilities[.. num_classes].iter().all(|p| p.is_finite() && *p > 0.0)
+                && (probabilities[.. num_classes].iter().sum::<f64>() - 1.0).abs() < 1e-6;
+
             // We first build up matrix Q as defined in (14) in the paper above. Q should have
             // the property of being a transition matrix for a Markov Chain.
             for t in 0 .. num_classes {
-                probabilities[t] = 1.0 / num_classes as f64;
+                if !warm_start {
+                    probabilities[t] = 1.0 / num_classes as f64;
+                }
 
                 q[(t, t)] = 0.0;
 
@@ -186,6 +1193,9 @@ macro_rules! impl_common_svm {
                     }
                 }
 
+                // Counts this round, so it is also set when the error below is returned.
+                problem.probability_iterations = i + 1;
+
                 if max_error < eps {
                     break;
                 }
@@ -193,19 +1203,26 @@ macro_rules! impl_common_svm {
                 // In case we are on the last iteration round past the threshold
                 // we know something went wrong. Signal we exceeded the threshold.
                 if i == max_iter {
-                    return Err(Error::IterationsExceeded);
+                    return Err(Error::IterationsExceeded {
+                        iterations: max_iter,
+                        max_error,
+                        eps,
+                    });
                 }
 
                 // This seems to be the main function performing (23) and (24).
                 for t in 0 .. num_classes {
                     let diff = (-qp[t] + pqp) / q[(t, t)];
 
+                    // Multiplying is considerably cheaper than dividing in the O(n^2) loop below.
+                    let scale = 1.0 / (1.0 + diff);
+
                     probabilities[t] += diff;
-                    pqp = (pqp + diff * (diff * q[(t, t)] + 2.0 * qp[t])) / (1.0 + diff) / (1.0 + diff);
+                    pqp = (pqp + diff * (diff * q[(t, t)] + 2.0 * qp[t])) * scale * scale;
 
                     for j in 0 .. num_classes {
-                        qp[j] = (qp[j] + diff * q[(t, j)]) / (1.0 + diff);
-                        probabilities[j] /= 1.0 + diff;
+                        qp[j] = (qp[j] + diff * q[(t, j)]) * scale;
+                        probabilities[j] *= scale;
                     }
                 }
             }
@@ -239,6 +1256,9 @@ macro_rules! impl_common_svm {
 
             for i in 0 .. self.classes.len() {
                 for j in (i + 1) .. self.classes.len() {
+                    // Coefficient matrices have `classes - 1` rows, which we validated when loading.
+                    debug_assert!(j >= 1 && j - 1 < self.classes.len() - 1);
+
                     let sv_coef0 = self.classes[i].coefficients.row(j - 1);
                     let sv_coef1 = self.classes[j].coefficients.row(i);
 
@@ -248,7 +1268,22 @@ macro_rules! impl_common_svm {
                     let sum0 = sv_coef0.iter().zip(kvalues0).map(|(a, b)| (*a * *b).sum()).sum::<f64>();
                     let sum1 = sv_coef1.iter().zip(kvalues1).map(|(a, b)| (*a * *b).sum()).sum::<f64>();
 
-                    let sum = sum0 + sum1 - self.rho[(i, j)];
+                    let mut sum = sum0 * self.classes[i].correction + sum1 * self.classes[j].correction - self.rho[(i, j)];
+
+                    let recompute = match self.precision {
+                        PrecisionMode::Fast => false,
+                        PrecisionMode::Adaptive { epsilon } => sum.abs() < epsilon,
+                        PrecisionMode::Precise => true,
+                    };
+
+                    if recompute {
+                        let sum0 = compensated_dot(self.classes[i].coefficients.row_as_flat(j - 1), problem.kernel_values.row_as_flat(i));
+                        let sum1 = compensated_dot(self.classes[j].coefficients.row_as_flat(i), problem.kernel_values.row_as_flat(j));
+
+                        sum = sum0 * self.classes[i].correction + sum1 * self.classes[j].correction - self.rho[(i, j)];
+                    }
+
+                    // Like libSVM, a decision value of exactly `0.0` votes for `j`.
                     let index_to_vote = if sum > 0.0 { i } else { j };
 
                     problem.decision_values[(i, j)] = sum;
@@ -257,19 +1292,56 @@ macro_rules! impl_common_svm {
             }
         }
 
-        /// Based on kernel values, computes the decision values for this problem.
-        crate fn compute_regression_values(&self, problem: &mut Problem<$v32>) {
+        /// Based on precomputed linear weights, computes the decision values for this problem.
+        crate fn compute_linear_classification_values(&self, problem: &mut Problem<$v32>) {
+            set_all(&mut problem.vote, 0);
+
+            for i in 0 .. self.classes.len() {
+                for j in (i + 1) .. self.classes.len() {
+                    // Only called once `enable_linear_fast` set weights for all classes.
+                    let weights0 = &self.classes[i].linear_weights.as_ref().unwrap()[j - 1];
+                    let weights1 = &self.classes[j].linear_weights.as_ref().unwrap()[i];
+
+                    let sum0 = problem.features.dot(weights0);
+                    let sum1 = problem.features.dot(weights1);
+
+                    let sum = sum0 * self.classes[i].correction + sum1 * self.classes[j].correction - self.rho[(i, j)];
+
+                    // Like libSVM, a decision value of exactly `0.0` votes for `j`.
+                    let index_to_vote = if sum > 0.0 { i } else { j };
+
+                    problem.decision_values[(i, j)] = sum;
+                    problem.vote[index_to_vote] += 1;
+                }
+            }
+        }
+
+        /// Based on kernel values, computes the only decision value of regression and one-class models.
+        crate fn compute_single_decision_value(&self, problem: &Problem<$v32>) -> f64 {
             let class = &self.classes[0];
             let coef = class.coefficients.row(0);
             let kvalues = problem.kernel_values.row(0);
 
-            let mut sum = coef.iter().zip(kvalues).map(|(a, b)| (*a * *b).sum()).sum::<f64>();
+            let sum = coef.iter().zip(kvalues).map(|(a, b)| (*a * *b).sum()).sum::<f64>();
+
+            sum - self.rho[0]
+        }
 
-            sum -= self.rho[0];
+        /// Based on kernel values, computes the decision values for this problem.
+        crate fn compute_regression_values(&self, problem: &mut Problem<$v32>) {
+            let sum = self.compute_single_decision_value(problem);
 
+            problem.regression_value = Some(sum);
             problem.result = Solution::Value(sum as f32);
         }
 
+        /// Based on kernel values, decides if this problem lies inside the learned region.
+        crate fn compute_one_class_values(&self, problem: &mut Problem<$v32>) {
+            let sum = self.compute_single_decision_value(problem);
+
+            problem.result = Solution::Inlier(sum > 0.0);
+        }
+
 
     };
 }
@@ -314,38 +1386,61 @@ macro_rules! impl_common_predict {
                     if num_classes == 2 {
                         problem_probabilities[0] = pairwise[(0, 1)];
                         problem_probabilities[1] = pairwise[(1, 0)];
+                        problem.probability_iterations = 0;
                     } else {
-                        self.compute_multiclass_probabilities(problem)?;
+                        self.compute_multiclass_probabilities(problem, self.probability_settings.max_iterations.max(num_classes))?;
                     }
 
                     let max_index = find_max_index(problem.probabilities.flat());
                     problem.result = Solution::Label(self.classes[max_index].label);
+                    problem.label_source = Some(LabelSource::Probabilities);
+
+                    Ok(())
+                }
+                // For regression libSVM only reports the noise scale `probA` on top of the value.
+                SVMType::ESvr | SVMType::NuSvr => {
+                    self.predict_value(problem)?;
+
+                    problem.regression_sigma = self.probabilities.as_ref().map(|probabilities| probabilities.a[0]);
 
                     Ok(())
                 }
                 // This fallback behavior is mandated by `libSVM`.
-                SVMType::ESvr | SVMType::NuSvr => self.predict_value(problem),
+                SVMType::OneClass => self.predict_value(problem),
             }
         }
 
 
         // Predict the value for one problem.
         fn predict_value(&self, problem: &mut Problem<$v32>) -> Result<(), Error> {
+            self.check_dimensions(problem)?;
+
             match self.svm_type {
                 SVMType::CSvc | SVMType::NuSvc => {
                     // Compute kernel, decision values and eventually the label
-                    self.compute_kernel_values(problem);
-                    self.compute_classification_values(problem);
+                    if self.classes[0].linear_weights.is_some() {
+                        self.compute_linear_classification_values(problem);
+                    } else {
+                        self.compute_kernel_values(problem);
+                        self.compute_classification_values(problem);
+                    }
 
                     // Compute highest vote
                     let highest_vote = find_max_index(&problem.vote);
                     problem.result = Solution::Label(self.classes[highest_vote].label);
+                    problem.label_source = Some(LabelSource::Votes);
 
                     Ok(())
                 }
                 SVMType::ESvr | SVMType::NuSvr => {
                     self.compute_kernel_values(problem);
                     self.compute_regression_values(problem);
+                    problem.regression_sigma = None;
+                    Ok(())
+                }
+                SVMType::OneClass => {
+                    self.compute_kernel_values(problem);
+                    self.compute_one_class_values(problem);
                     Ok(())
                 }
             }
@@ -355,45 +1450,151 @@ macro_rules! impl_common_predict {
 }
 
 macro_rules! prepare_svm {
-    ($raw_model:expr, $k:ty, $m32:ty) => {
+    ($raw_model:expr, $by_index:expr, $k:ty, $m32:ty) => {
+        prepare_svm!($raw_model, $by_index, $k, $m32, {
+            let kernel: (Box<$k>, KernelInfo) = match $raw_model.header.kernel_type.as_ref() {
+                "rbf" => {
+                    let kernel = Rbf::try_from($raw_model)?;
+                    (Box::new(kernel), KernelInfo::from(kernel))
+                }
+                "linear" => {
+                    let kernel = Linear::from($raw_model);
+                    (Box::new(kernel), KernelInfo::from(kernel))
+                }
+                "polynomial" => {
+                    let kernel = Poly::try_from($raw_model)?;
+                    (Box::new(kernel), KernelInfo::from(kernel))
+                }
+                "sigmoid" => {
+                    let kernel = Sigmoid::try_from($raw_model)?;
+                    (Box::new(kernel), KernelInfo::from(kernel))
+                }
+                "precomputed" => {
+                    let kernel = Precomputed::from($raw_model);
+                    (Box::new(kernel), KernelInfo::from(kernel))
+                }
+                _ => return Err(Error::MalformedModel { field: "kernel_type" }),
+            };
+
+            kernel
+        })
+    };
+
+    // Same as above, but with the kernel and its info given explicitly.
+    ($raw_model:expr, $by_index:expr, $k:ty, $m32:ty, $kernel:expr) => {
         // To quickly check what broke again during parsing ...
         // println!("{:?}", raw_model);
         {
             let header = &$raw_model.header;
             let vectors = &$raw_model.vectors;
 
+            // Degenerate models, e.g., from misconfigured training runs, can't be used.
+            if header.total_sv == 0 || vectors.is_empty() {
+                return Err(Error::EmptyModel);
+            }
+
             // Get basic info
-            let num_attributes = vectors[0].features.len();
+            // Support vectors may omit trailing attributes, so the widest one (which is not
+            // necessarily the first) determines the number of attributes. Attributes are either
+            // stored at their index, or (dense models by default) at their position.
+            let num_attributes = match header.kernel_type.as_ref() {
+                // Precomputed models only store training sample ids as attribute 0, while
+                // problems hold one kernel value per sample id.
+                "precomputed" => vectors
+                    .iter()
+                    .filter_map(|vector| vector.features.first())
+                    .map(|attribute| attribute.value as usize + 1)
+                    .max()
+                    .unwrap_or(0),
+                _ if $by_index => vectors
+                    .iter()
+                    .filter_map(|vector| vector.features.last())
+                    .map(|attribute| attribute.index as usize + 1)
+                    .max()
+                    .unwrap_or(0),
+                _ => vectors.iter().map(|vector| vector.features.len()).max().unwrap_or(0),
+            };
             let num_total_sv = header.total_sv as usize;
 
-            let svm_type = match $raw_model.header.svm_type {
-                "c_svc" => SVMType::CSvc,
-                "nu_svc" => SVMType::NuSvc,
-                "epsilon_svr" => SVMType::ESvr,
-                "nu_svr" => SVMType::NuSvr,
-                _ => unimplemented!(),
-            };
+            let svm_type = $raw_model.header.svm_type.parse::<SVMType>()?;
 
-            let kernel: Box<$k> = match $raw_model.header.kernel_type {
-                "rbf" => Box::new(Rbf::try_from($raw_model)?),
-                "linear" => Box::new(Linear::from($raw_model)),
-                "polynomial" => Box::new(Poly::try_from($raw_model)?),
-                "sigmoid" => Box::new(Sigmoid::try_from($raw_model)?),
-                _ => unimplemented!(),
-            };
+            let (kernel, kernel_info): (Box<$k>, KernelInfo) = $kernel;
 
             let num_classes = match svm_type {
                 SVMType::CSvc | SVMType::NuSvc => header.nr_class as usize,
-                // For SVRs we set number of classes to 1, since that resonates better
-                // with our internal handling
-                SVMType::ESvr | SVMType::NuSvr => 1,
+                // For SVRs and one-class SVMs we set number of classes to 1, since that
+                // resonates better with our internal handling
+                SVMType::ESvr | SVMType::NuSvr | SVMType::OneClass => 1,
             };
 
+            // Classifiers need at least two classes, single-class data should be modelled with
+            // a one-class SVM instead.
+            if svm_type.is_classifier() && num_classes < 2 {
+                return Err(Error::InsufficientClasses { found: num_classes });
+            }
+
+            // Header lists must match `nr_class` (and support vectors `total_sv`), otherwise
+            // corrupt or fuzzed models would make us index out of bounds below.
+            let num_pairs = num_classes.max(2) * (num_classes.max(2) - 1) / 2;
+            let malformed = |field| Err(Error::MalformedModel { field });
+
+            if vectors.len() != num_total_sv {
+                return malformed("total_sv");
+            }
+
+            if svm_type.is_classifier() {
+                if header.label.len() != num_classes {
+                    return malformed("label");
+                }
+
+                if header.nr_sv.len() != num_classes || header.nr_sv.iter().map(|n| *n as usize).sum::<usize>() != num_total_sv {
+                    return malformed("nr_sv");
+                }
+            }
+
+            let num_rho = if svm_type.is_classifier() { num_pairs } else { 1 };
+
+            if header.rho.len() != num_rho {
+                return malformed("rho");
+            }
+
+            match (svm_type, &header.prob_a, &header.prob_b) {
+                (_, None, None) | (SVMType::OneClass, _, _) => {}
+                (SVMType::CSvc, Some(a), Some(b)) | (SVMType::NuSvc, Some(a), Some(b)) => {
+                    if a.len() != num_pairs {
+                        return malformed("probA");
+                    }
+
+                    if b.len() != num_pairs {
+                        return malformed("probB");
+                    }
+                }
+                (SVMType::CSvc, None, _) | (SVMType::NuSvc, None, _) => return malformed("probA"),
+                (SVMType::CSvc, _, None) | (SVMType::NuSvc, _, None) => return malformed("probB"),
+                (SVMType::ESvr, Some(a), None) | (SVMType::NuSvr, Some(a), None) if a.len() == 1 => {}
+                (_, _, Some(_)) => return malformed("probB"),
+                (_, _, None) => return malformed("probA"),
+            }
+
+            // Each support vector needs exactly one coefficient per decision function it takes
+            // part in. Otherwise the model is corrupt and we would index out of bounds later.
+            let num_coefficients = num_classes.max(2) - 1;
+
+            for (vector_index, vector) in vectors.iter().enumerate() {
+                if vector.coefs.len() != num_coefficients {
+                    return Err(Error::CoefficientMatrixShape {
+                        vector_index,
+                        expected: num_coefficients,
+                        actual: vector.coefs.len(),
+                    });
+                }
+            }
+
             let nr_sv = match svm_type {
                 SVMType::CSvc | SVMType::NuSvc => header.nr_sv.clone(),
-                // For SVRs we set number of classes to 1, since that resonates better
-                // with our internal handling
-                SVMType::ESvr | SVMType::NuSvr => vec![num_total_sv as u32],
+                // For SVRs and one-class SVMs we set number of classes to 1, since that
+                // resonates better with our internal handling
+                SVMType::ESvr | SVMType::NuSvr | SVMType::OneClass => vec![num_total_sv as u32],
             };
 
             // Construct vector of classes
@@ -406,7 +1607,7 @@ macro_rules! prepare_svm {
                         Class::<$m32>::with_parameters(num_classes, num_sv, num_attributes, label)
                     })
                     .collect::<Vec<Class<$m32>>>(),
-                SVMType::ESvr | SVMType::NuSvr => vec![Class::<$m32>::with_parameters(
+                SVMType::ESvr | SVMType::NuSvr | SVMType::OneClass => vec![Class::<$m32>::with_parameters(
                     2,
                     num_total_sv,
                     num_attributes,
@@ -436,7 +1637,14 @@ macro_rules! prepare_svm {
                     num_attributes,
                     probabilities,
                     kernel,
+                    kernel_info,
                     svm_type,
+                    precision: PrecisionMode::default(),
+                    kernel_precision: KernelPrecision::default(),
+                    probability_warm_start: false,
+                    probability_settings: ProbabilitySettings::default(),
+                    parallel_kernels: false,
+                    metadata: std::collections::HashMap::new(),
                     rho: Triangular::from(&header.rho),
                     classes,
                     phantom_v32: PhantomData,
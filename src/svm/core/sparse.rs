@@ -1,30 +1,165 @@
 use crate::sparse::{SparseMatrix, SparseVector};
 
-use std::{convert::TryFrom, marker::PhantomData};
+use std::{convert::TryFrom, fmt, marker::PhantomData};
 
 use crate::{
     errors::Error,
-    parser::ModelFile,
+    parser::{ModelFile, SupportVector},
     svm::{
         class::Class,
-        core::SVMCore,
-        kernel::{KernelSparse, Linear, Poly, Rbf, Sigmoid},
+        core::{SVMCore, Settings},
+        kernel::{KernelSparse, KernelInfo, Linear, Poly, Precomputed, Rbf, Sigmoid},
         predict::Predict,
-        problem::{Problem, Solution},
-        Probabilities, SVMType, SparseSVM,
+        problem::{LabelSource, Problem, Solution},
+        DenseSVM, KernelPrecision, PrecisionMode, Probabilities, ProbabilitySettings, SVMType, SparseSVM,
     },
-    util::{find_max_index, set_all, sigmoid_predict},
+    util::{compensated_dot, find_max_index, set_all, sigmoid_predict},
     vectors::Triangular,
 };
 
 impl SparseSVM {
     impl_common_svm!(SparseVector<f32>);
+
+    /// The minimum [SparseSVM::density] for which [SparseSVM::to_dense] converts a model.
+    pub const MIN_DENSITY: f64 = 0.1;
+
+    /// Computes how entangled the support vectors of two classes are.
+    ///
+    /// # Description
+    ///
+    /// Returns the mean kernel value over all pairs of support vectors of class index `i`
+    /// and class index `j`, using the model's kernel. Higher values (e.g., close to `1.0` for
+    /// RBF kernels) indicate the two classes are hard to separate and will likely be confused.
+    ///
+    /// # Returns
+    ///
+    /// The mean kernel value, or `None` if either index is not a valid class index.
+    pub fn class_overlap(&self, i: usize, j: usize) -> Option<f64> {
+        let class_i = self.classes.get(i)?;
+        let class_j = self.classes.get(j)?;
+
+        let mut kernel_values = vec![0.0; class_j.num_support_vectors];
+        let mut sum = 0.0;
+
+        for feature in class_i.support_vectors.row_iter() {
+            self.kernel.compute(&class_j.support_vectors, feature, &mut kernel_values);
+            sum += kernel_values.iter().sum::<f64>();
+        }
+
+        Some(sum / (class_i.num_support_vectors * class_j.num_support_vectors).max(1) as f64)
+    }
+
+    /// Groups the support vectors of each class by their number of non-zero attributes.
+    ///
+    /// # Description
+    ///
+    /// Sparse kernels walk the non-zero entries of every support vector. If the lengths of these
+    /// runs vary wildly, branch prediction suffers. After calling this method, support vectors
+    /// of similar density are adjacent within their class, which keeps these loops regular and
+    /// improves cache behavior.
+    ///
+    /// Coefficients are permuted alongside, so predictions stay the same up to the order
+    /// in which decision values are summed.
+    pub fn group_support_vectors(&mut self) {
+        // Classifiers have one coefficient per other class, regression and one-class models one.
+        let num_coefficients = self.classes.len().max(2) - 1;
+
+        for class in &mut self.classes {
+            let mut order = (0 .. class.num_support_vectors).collect::<Vec<_>>();
+            order.sort_by_key(|&i| class.support_vectors.row(i).len());
+
+            class.support_vectors.permute_rows(&order);
+
+            let mut coefficients = class.coefficients.flat_mut();
+
+            for i_coefficient in 0 .. num_coefficients {
+                let original = order.iter().map(|&i| coefficients[(i_coefficient, i)]).collect::<Vec<f64>>();
+
+                for (i_vector, coefficient) in original.into_iter().enumerate() {
+                    coefficients[(i_coefficient, i_vector)] = coefficient;
+                }
+            }
+        }
+    }
+
+    /// Returns the fraction of support vector attributes that are stored, i.e., non-zero.
+    ///
+    /// `1.0` means every support vector has a value for every attribute, so the model is
+    /// effectively dense and [SparseSVM::to_dense] is worth considering.
+    pub fn density(&self) -> f64 {
+        let stored = self.classes.iter().flat_map(|class| class.support_vectors.row_iter()).map(|vector| vector.len()).sum::<usize>();
+
+        stored as f64 / (self.num_total_sv * self.num_attributes).max(1) as f64
+    }
+
+    /// Converts this SVM into a [DenseSVM], which predicts considerably faster on dense data.
+    ///
+    /// # Description
+    ///
+    /// Missing attributes are filled in with `0.0`, as libSVM assumes for omitted attributes, so
+    /// both SVMs predict the same. Settings such as [PrecisionMode], class frequency corrections,
+    /// probability settings, linear fast mode and metadata are carried over.
+    ///
+    /// Densifying a mostly empty model wastes memory and makes prediction slower, so the
+    /// conversion is refused if less than [SparseSVM::MIN_DENSITY] of all attributes are stored,
+    /// see [SparseSVM::density].
+    ///
+    /// # Returns
+    ///
+    /// The dense SVM, [Error::TooSparse] for models below the minimum density, or
+    /// [Error::CustomKernel] for models using a kernel set via `from_parts`.
+    pub fn to_dense(&self) -> Result<DenseSVM, Error> {
+        if self.kernel_info == KernelInfo::Custom {
+            return Err(Error::CustomKernel);
+        }
+
+        let density = self.density();
+
+        if density < SparseSVM::MIN_DENSITY {
+            return Err(Error::TooSparse { density });
+        }
+
+        // Same state serialization carries over, see `DenseSVM`'s `Serialize` implementation.
+        let mut dense = DenseSVM::try_from(&self.to_model_file()?)?;
+        dense.apply_settings(self.settings())?;
+
+        Ok(dense)
+    }
 }
 
 impl Predict<SparseVector<f32>, SparseVector<f64>> for SparseSVM {
     impl_common_predict!(SparseVector<f32>);
 }
 
+/// Writes the SVM in libSVM's model format, so `svm.to_string()` can be loaded again. Fails
+/// for custom kernels, see [SparseSVM::to_model_string].
+impl fmt::Display for SparseSVM {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_header(f)?;
+
+        // Classifiers have one coefficient per other class, regression and one-class models one.
+        let num_coefficients = (self.classes.len() - 1).max(1);
+
+        for class in &self.classes {
+            let coefficients = class.coefficients.flat();
+
+            for (i_vector, vector) in class.support_vectors.row_iter().enumerate() {
+                for i_coefficient in 0 .. num_coefficients {
+                    write!(f, "{} ", coefficients[(i_coefficient, i_vector)])?;
+                }
+
+                for (index, value) in vector.iter() {
+                    write!(f, "{}:{} ", index, value)?;
+                }
+
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a, 'b> TryFrom<&'a str> for SparseSVM {
     type Error = Error;
 
@@ -38,7 +173,7 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for SparseSVM {
     type Error = Error;
 
     fn try_from(raw_model: &'a ModelFile<'_>) -> Result<SparseSVM, Error> {
-        let (mut svm, nr_sv) = prepare_svm!(raw_model, dyn KernelSparse, SparseMatrix<f32>);
+        let (mut svm, nr_sv) = prepare_svm!(raw_model, true, dyn KernelSparse, SparseMatrix<f32>);
 
         let vectors = &raw_model.vectors;
 
@@ -52,16 +187,30 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for SparseSVM {
 
             // Set support vector and coefficients
             for (i_vector, vector) in vectors[start_offset .. stop_offset].iter().enumerate() {
-                // Set support vectors
+                let mut last_attribute = None;
+
+                // Set support vectors, gaps between indices are zero-valued attributes.
                 for attribute in &vector.features {
+                    if let Some(last) = last_attribute {
+                        if attribute.index <= last {
+                            return Result::Err(Error::AttributesUnordered {
+                                index: attribute.index,
+                                value: attribute.value,
+                                last_index: last,
+                            });
+                        }
+                    };
+
                     let support_vectors = &mut svm.classes[i].support_vectors;
-                    support_vectors[(i_vector, attribute.index as usize)] = attribute.value;
+                    support_vectors[(i_vector, attribute.index as usize)] = attribute.value as f32;
+
+                    last_attribute = Some(attribute.index);
                 }
 
                 // Set coefficients
                 for (i_coefficient, coefficient) in vector.coefs.iter().enumerate() {
                     let mut coefficients = svm.classes[i].coefficients.flat_mut();
-                    coefficients[(i_coefficient, i_vector)] = f64::from(*coefficient);
+                    coefficients[(i_coefficient, i_vector)] = *coefficient;
                 }
             }
 
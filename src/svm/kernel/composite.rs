@@ -0,0 +1,153 @@
+use super::{KernelDense, KernelSparse};
+use crate::{
+    sparse::{SparseMatrix, SparseVector},
+    util::set_all,
+};
+
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
+
+/// Weighted sum of multiple kernels, as used by multiple kernel learning (MKL).
+///
+/// Each component kernel is computed separately and its values are added to the output,
+/// scaled by the component's weight. Component values are kept in the scratch buffer of the
+/// [Problem] being predicted, so only its first prediction allocates. Calling
+/// [KernelDense::compute] directly, or predicting with parallel kernels, allocates a new
+/// scratch buffer per computation.
+///
+/// ```ignore
+/// let kernel = CompositeKernel::<dyn KernelDense>::new()
+///     .with_kernel(0.5, Box::new(Linear::default()))
+///     .with_kernel(0.5, Box::new(Rbf { gamma: 0.1 }));
+/// ```
+#[doc(hidden)]
+pub struct CompositeKernel<K>
+where
+    K: ?Sized,
+{
+    crate components: Vec<(f64, Box<K>)>,
+}
+
+impl<K> CompositeKernel<K>
+where
+    K: ?Sized,
+{
+    /// Creates an empty composite kernel.
+    pub fn new() -> Self { CompositeKernel { components: Vec::new() } }
+
+    /// Adds another kernel with the given weight.
+    pub fn with_kernel(mut self, weight: f64, kernel: Box<K>) -> Self {
+        self.components.push((weight, kernel));
+        self
+    }
+}
+
+impl<K> Default for CompositeKernel<K>
+where
+    K: ?Sized,
+{
+    fn default() -> Self { CompositeKernel::new() }
+}
+
+/// Computes all components into `scratch` via `$compute`, and accumulates them, weighted, into `output`.
+macro_rules! accumulate_components {
+    ($self:expr, $output:expr, $scratch:expr, |$kernel:ident, $values:ident| $compute:expr) => {{
+        let scratch: &mut Vec<f64> = $scratch;
+
+        if scratch.len() < $output.len() {
+            scratch.resize($output.len(), 0.0);
+        }
+
+        let $values = &mut scratch[.. $output.len()];
+
+        set_all($output, 0.0);
+
+        for (weight, $kernel) in &$self.components {
+            // Kernels only write one value per support vector, the rest must not be stale.
+            set_all($values, 0.0);
+            $compute;
+
+            for (x, y) in $output.iter_mut().zip($values.iter()) {
+                *x += weight * y;
+            }
+        }
+    }};
+}
+
+impl KernelDense for CompositeKernel<dyn KernelDense> {
+    fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        self.compute_with_scratch(vectors, feature, output, &mut Vec::new());
+    }
+
+    fn compute_f64(&self, vectors: &SimdMatrix<f32s, RowOptimized>, vectors_f64: &SimdMatrix<f64s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        self.compute_f64_with_scratch(vectors, vectors_f64, feature, output, &mut Vec::new());
+    }
+
+    fn compute_with_scratch(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64], scratch: &mut Vec<f64>) {
+        accumulate_components!(self, output, scratch, |kernel, values| kernel.compute(vectors, feature, values));
+    }
+
+    fn compute_f64_with_scratch(
+        &self,
+        vectors: &SimdMatrix<f32s, RowOptimized>,
+        vectors_f64: &SimdMatrix<f64s, RowOptimized>,
+        feature: &SimdVector<f32s>,
+        output: &mut [f64],
+        scratch: &mut Vec<f64>,
+    ) {
+        accumulate_components!(self, output, scratch, |kernel, values| kernel.compute_f64(vectors, vectors_f64, feature, values));
+    }
+}
+
+impl KernelSparse for CompositeKernel<dyn KernelSparse> {
+    fn compute(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64]) {
+        self.compute_with_scratch(vectors, feature, output, &mut Vec::new());
+    }
+
+    fn compute_with_scratch(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64], scratch: &mut Vec<f64>) {
+        accumulate_components!(self, output, scratch, |kernel, values| kernel.compute(vectors, feature, values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompositeKernel;
+    use crate::svm::kernel::{KernelDense, Linear, Rbf};
+    use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+
+    #[test]
+    fn weighted_average_of_components() {
+        let mut vectors = SimdMatrix::<f32s, RowOptimized>::with_dimension(3, 4);
+        let mut feature = SimdVector::<f32s>::with(0.0, 4);
+
+        {
+            let mut flat = vectors.flat_mut();
+
+            for i in 0 .. 3 {
+                for j in 0 .. 4 {
+                    flat[(i, j)] = (i * 4 + j) as f32 / 10.0;
+                }
+            }
+        }
+
+        feature.flat_mut().clone_from_slice(&[0.1, 0.2, 0.3, 0.4]);
+
+        let linear = Linear::default();
+        let rbf = Rbf { gamma: 0.5 };
+        let composite = CompositeKernel::<dyn KernelDense>::new()
+            .with_kernel(0.5, Box::new(linear))
+            .with_kernel(0.5, Box::new(rbf));
+
+        let mut linear_values = [0.0; 3];
+        let mut rbf_values = [0.0; 3];
+        let mut composite_values = [0.0; 3];
+
+        KernelDense::compute(&linear, &vectors, &feature, &mut linear_values);
+        KernelDense::compute(&rbf, &vectors, &feature, &mut rbf_values);
+        composite.compute(&vectors, &feature, &mut composite_values);
+
+        for i in 0 .. 3 {
+            let expected = (linear_values[i] + rbf_values[i]) / 2.0;
+            assert!((composite_values[i] - expected).abs() < 1e-9);
+        }
+    }
+}
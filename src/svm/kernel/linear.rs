@@ -1,12 +1,12 @@
 use std::convert::From;
 
-use super::{KernelDense, KernelSparse};
+use super::{dot_f64, KernelDense, KernelInfo, KernelSparse};
 use crate::{
     parser::ModelFile,
     sparse::{SparseMatrix, SparseVector},
 };
 
-use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
 
 #[derive(Copy, Clone, Debug, Default)]
 #[doc(hidden)]
@@ -25,6 +25,12 @@ impl KernelDense for Linear {
             output[i] = f64::from(sum.sum());
         }
     }
+
+    fn compute_f64(&self, _vectors: &SimdMatrix<f32s, RowOptimized>, vectors_f64: &SimdMatrix<f64s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        for (i, _) in vectors_f64.row_iter().enumerate() {
+            output[i] = dot_f64(vectors_f64.row_as_flat(i), feature.flat());
+        }
+    }
 }
 
 impl KernelSparse for Linear {
@@ -56,3 +62,7 @@ impl KernelSparse for Linear {
 impl<'a> From<&'a ModelFile<'a>> for Linear {
     fn from(_model: &'a ModelFile<'a>) -> Self { Linear {} }
 }
+
+impl From<Linear> for KernelInfo {
+    fn from(_kernel: Linear) -> Self { KernelInfo::Linear }
+}
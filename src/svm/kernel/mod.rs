@@ -1,27 +1,200 @@
+mod composite;
 mod linear;
 mod poly;
+mod precomputed;
 mod rbf;
 mod sigmoid;
 
 use crate::sparse::{SparseMatrix, SparseVector};
-use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
+use std::fmt;
 
-pub use self::{linear::*, poly::*, rbf::*, sigmoid::*};
+pub use self::{composite::*, linear::*, poly::*, precomputed::*, rbf::*, sigmoid::*};
 
-/// Base trait for kernels
-#[doc(hidden)]
+/// Base trait for kernels of dense SVMs.
+///
+/// # Description
+///
+/// Besides the built-in kernels, you can implement this trait for your own kernel and use it
+/// via [DenseSVM::from_parts]. `SimdMatrix` and `SimdVector` come from the
+/// [simd_aligned](https://crates.io/crates/simd_aligned) crate. Kernels must be `Send + Sync` so
+/// SVMs can be shared between threads, e.g., via [PredictPool].
+///
+/// ```
+/// use ffsvm::KernelDense;
+/// use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+///
+/// /// Chi-squared kernel for histograms, `exp(-gamma * sum((u - v)^2 / (u + v)))`.
+/// struct ChiSquared {
+///     gamma: f32,
+/// }
+///
+/// impl KernelDense for ChiSquared {
+///     fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+///         for (i, sv) in vectors.row_iter().enumerate() {
+///             let sum = sv
+///                 .iter()
+///                 .zip(feature.iter())
+///                 .flat_map(|(a, b)| (0 .. f32s::lanes()).map(move |l| (a.extract(l), b.extract(l))))
+///                 .filter(|(a, b)| a + b > 0.0)
+///                 .map(|(a, b)| (a - b) * (a - b) / (a + b))
+///                 .sum::<f32>();
+///
+///             output[i] = f64::from((-self.gamma * sum).exp());
+///         }
+///     }
+/// }
+/// ```
 pub trait KernelDense
 where
-    Self: Sync,
+    Self: Send + Sync,
 {
+    /// Computes the kernel value between each support vector and the feature vector.
+    ///
+    /// `vectors` holds one support vector per row, `feature` the problem's features. Both are
+    /// padded with zeros up to a multiple of the SIMD width. `output` has at least one entry per
+    /// row of `vectors`, and the kernel value of row `i` must be written to `output[i]`. Any
+    /// entries past the number of rows must be left alone.
     fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]);
+
+    /// Like [KernelDense::compute], but in `f64`, see [KernelPrecision::Double].
+    ///
+    /// `vectors_f64` holds the same support vectors as `vectors`, but with the `f64` values of
+    /// the model file. Kernel parameters and all arithmetic should be `f64` as well.
+    ///
+    /// Defaults to [KernelDense::compute], so kernels without a dedicated implementation are
+    /// computed in their usual precision.
+    fn compute_f64(&self, vectors: &SimdMatrix<f32s, RowOptimized>, _vectors_f64: &SimdMatrix<f64s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        self.compute(vectors, feature, output)
+    }
+
+    /// Like [KernelDense::compute], but may use `scratch` as temporary storage. Predictions pass
+    /// a buffer owned by the [Problem], so kernels needing temporary storage (e.g.,
+    /// [CompositeKernel]) don't have to allocate on every call.
+    ///
+    /// Defaults to [KernelDense::compute].
+    fn compute_with_scratch(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64], _scratch: &mut Vec<f64>) {
+        self.compute(vectors, feature, output)
+    }
+
+    /// Like [KernelDense::compute_with_scratch], but in `f64`, see [KernelDense::compute_f64].
+    ///
+    /// Defaults to [KernelDense::compute_f64].
+    fn compute_f64_with_scratch(
+        &self,
+        vectors: &SimdMatrix<f32s, RowOptimized>,
+        vectors_f64: &SimdMatrix<f64s, RowOptimized>,
+        feature: &SimdVector<f32s>,
+        output: &mut [f64],
+        _scratch: &mut Vec<f64>,
+    ) {
+        self.compute_f64(vectors, vectors_f64, feature, output)
+    }
 }
 
 /// Base trait for kernels
 #[doc(hidden)]
 pub trait KernelSparse
 where
-    Self: Sync,
+    Self: Send + Sync,
 {
     fn compute(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64]);
+
+    /// Like [KernelSparse::compute], but may use `scratch` as temporary storage, see [KernelDense::compute_with_scratch].
+    ///
+    /// Sparse kernels are always computed in their usual precision, there is no `f64` variant.
+    fn compute_with_scratch(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64], _scratch: &mut Vec<f64>) {
+        self.compute(vectors, feature, output)
+    }
+}
+
+/// Mask for the last SIMD vector of `num_attributes` attributes, `1.0` for attribute lanes and
+/// `0.0` for padding lanes.
+crate fn padding_mask(num_attributes: usize) -> f32s {
+    let used = match num_attributes % f32s::lanes() {
+        0 => f32s::lanes(),
+        n => n,
+    };
+
+    (used .. f32s::lanes()).fold(f32s::splat(1.0), |mask, lane| mask.replace(lane, 0.0))
+}
+
+/// Squared euclidean distance between `a` and `b`, with the last SIMD vector multiplied by
+/// `mask` (see [padding_mask]) so padding never contributes, whatever it holds.
+crate fn squared_distance(a: &[f32s], b: &[f32s], mask: f32s) -> f32 {
+    match (a.split_last(), b.split_last()) {
+        (Some((a_last, a)), Some((b_last, b))) => {
+            let mut sum = f32s::splat(0.0);
+
+            for (x, y) in a.iter().zip(b) {
+                sum += (*x - *y) * (*x - *y);
+            }
+
+            let last = (*a_last - *b_last) * mask;
+
+            (sum + last * last).sum()
+        }
+        _ => 0.0,
+    }
+}
+
+/// Dot product of `a` and `b`, computed in `f64`.
+crate fn dot_f64(a: &[f64], b: &[f32]) -> f64 { a.iter().zip(b).map(|(x, y)| x * f64::from(*y)).sum() }
+
+/// Squared euclidean distance between `a` and `b`, computed in `f64`.
+crate fn squared_distance_f64(a: &[f64], b: &[f32]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - f64::from(*y)).powi(2)).sum()
+}
+
+/// Kernel family and parameters of a model, as found in its header.
+///
+/// Returned by [SVMCore::kernel_info] for logging or sanity checks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KernelInfo {
+    /// Linear kernel, `u'v`.
+    Linear,
+
+    /// Polynomial kernel, `(gamma * u'v + coef0)^degree`.
+    Poly { gamma: f64, coef0: f64, degree: u32 },
+
+    /// Radial basis function kernel, `exp(-gamma * |u-v|^2)`.
+    Rbf { gamma: f64 },
+
+    /// Sigmoid kernel, `tanh(gamma * u'v + coef0)`.
+    Sigmoid { gamma: f64, coef0: f64 },
+
+    /// Precomputed kernel values given as features, see [Precomputed].
+    Precomputed,
+
+    /// A user supplied kernel, see [DenseSVM::from_parts].
+    Custom,
+}
+
+impl KernelInfo {
+    /// Returns the kernel parameters `(gamma, coef0, degree)` as stored in a model header.
+    crate fn parameters(self) -> (Option<f64>, Option<f64>, Option<u32>) {
+        match self {
+            KernelInfo::Poly { gamma, coef0, degree } => (Some(gamma), Some(coef0), Some(degree)),
+            KernelInfo::Rbf { gamma } => (Some(gamma), None, None),
+            KernelInfo::Sigmoid { gamma, coef0 } => (Some(gamma), Some(coef0), None),
+            KernelInfo::Linear | KernelInfo::Precomputed | KernelInfo::Custom => (None, None, None),
+        }
+    }
+}
+
+/// Writes the `kernel_type` name libSVM uses, e.g., `"rbf"`. [KernelInfo::Custom] is written
+/// as `"custom"` for diagnostics only, SVMs using it can't be written as model files.
+impl fmt::Display for KernelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KernelInfo::Linear => "linear",
+            KernelInfo::Poly { .. } => "polynomial",
+            KernelInfo::Rbf { .. } => "rbf",
+            KernelInfo::Sigmoid { .. } => "sigmoid",
+            KernelInfo::Precomputed => "precomputed",
+            KernelInfo::Custom => "custom",
+        };
+
+        write!(f, "{}", name)
+    }
 }
@@ -1,24 +1,26 @@
 use std::convert::{From, TryFrom};
 
-use super::{KernelDense, KernelSparse};
+use super::{dot_f64, KernelDense, KernelInfo, KernelSparse};
 use crate::{
     errors::Error,
     parser::ModelFile,
     sparse::{SparseMatrix, SparseVector},
 };
 
-use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
 
 #[derive(Copy, Clone, Debug, Default)]
 #[doc(hidden)]
 pub struct Poly {
     degree: u32,
-    gamma: f32,
-    coef0: f32,
+    gamma: f64,
+    coef0: f64,
 }
 
 impl KernelDense for Poly {
     fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        let (gamma, coef0) = (self.gamma as f32, self.coef0 as f32);
+
         for (i, sv) in vectors.row_iter().enumerate() {
             let mut sum = f32s::splat(0.0);
             let feature: &[f32s] = &feature;
@@ -27,13 +29,23 @@ impl KernelDense for Poly {
                 sum += *a * *b;
             }
 
-            output[i] = crate::util::powi(f64::from(self.gamma * sum.sum() + self.coef0), self.degree);
+            output[i] = crate::util::powi(f64::from(gamma * sum.sum() + coef0), self.degree);
+        }
+    }
+
+    fn compute_f64(&self, _vectors: &SimdMatrix<f32s, RowOptimized>, vectors_f64: &SimdMatrix<f64s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        let (gamma, coef0) = (self.gamma, self.coef0);
+
+        for (i, _) in vectors_f64.row_iter().enumerate() {
+            output[i] = crate::util::powi(gamma * dot_f64(vectors_f64.row_as_flat(i), feature.flat()) + coef0, self.degree);
         }
     }
 }
 
 impl KernelSparse for Poly {
     fn compute(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64]) {
+        let (gamma, coef0) = (self.gamma as f32, self.coef0 as f32);
+
         for (i, sv) in vectors.row_iter().enumerate() {
             let mut sum = 0.0;
             let mut a_iter = sv.iter();
@@ -51,7 +63,7 @@ impl KernelSparse for Poly {
                     }
                     (Some((i_a, _)), Some((i_b, _))) if i_a < i_b => a = a_iter.next(),
                     (Some((i_a, _)), Some((i_b, _))) if i_a > i_b => b = b_iter.next(),
-                    _ => break crate::util::powi(f64::from(self.gamma * sum + self.coef0), self.degree),
+                    _ => break crate::util::powi(f64::from(gamma * sum + coef0), self.degree),
                 }
             }
         }
@@ -69,3 +81,13 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for Poly {
         Ok(Poly { gamma, coef0, degree })
     }
 }
+
+impl From<Poly> for KernelInfo {
+    fn from(kernel: Poly) -> Self {
+        KernelInfo::Poly {
+            gamma: kernel.gamma,
+            coef0: kernel.coef0,
+            degree: kernel.degree,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::convert::From;
+
+use super::{KernelDense, KernelInfo, KernelSparse};
+use crate::{
+    parser::ModelFile,
+    sparse::{SparseMatrix, SparseVector},
+};
+
+use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+
+/// Kernel for models trained with libSVM's precomputed kernels (`svm-train -t 4`).
+///
+/// Such models only store the (1-based) training sample id of each support vector as attribute
+/// `0`. Accordingly, the features of a [Problem] must hold the precomputed kernel values instead,
+/// with feature `i` being the kernel value between the problem and training sample `i`. Feature
+/// `0` (the problem's own sample id in libSVM's format) is ignored.
+#[derive(Copy, Clone, Debug, Default)]
+#[doc(hidden)]
+pub struct Precomputed {}
+
+impl KernelDense for Precomputed {
+    fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        let feature = feature.flat();
+
+        for (i, sv) in vectors.row_iter().enumerate() {
+            let sample_id = sv[0].extract(0) as usize;
+
+            output[i] = f64::from(feature[sample_id]);
+        }
+    }
+}
+
+impl KernelSparse for Precomputed {
+    fn compute(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64]) {
+        for (i, sv) in vectors.row_iter().enumerate() {
+            let sample_id = sv.iter().next().map_or(0, |(_, id)| id as u32);
+
+            // Missing features are zero in sparse problems.
+            output[i] = feature.iter().find(|(index, _)| *index == sample_id).map_or(0.0, |(_, value)| f64::from(value));
+        }
+    }
+}
+
+impl<'a> From<&'a ModelFile<'a>> for Precomputed {
+    fn from(_model: &'a ModelFile<'a>) -> Self { Precomputed {} }
+}
+
+impl From<Precomputed> for KernelInfo {
+    fn from(_kernel: Precomputed) -> Self { KernelInfo::Precomputed }
+}
@@ -1,42 +1,49 @@
 use std::convert::{From, TryFrom};
 
-use super::{KernelDense, KernelSparse};
+use super::{padding_mask, squared_distance, squared_distance_f64, KernelDense, KernelInfo, KernelSparse};
 use crate::{
     errors::Error,
     parser::ModelFile,
     sparse::{SparseMatrix, SparseVector},
 };
 
-use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
 
 #[derive(Copy, Clone, Debug, Default)]
 #[doc(hidden)]
 pub struct Rbf {
-    pub gamma: f32,
+    pub gamma: f64,
 }
 
 impl KernelDense for Rbf {
     fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        let gamma = self.gamma as f32;
+        let mask = padding_mask(feature.flat().len());
+        let feature: &[f32s] = &feature;
+
         // According to Instruments, for realistic SVMs and problems, the VAST majority of our
         // CPU time is spent in this loop.
         for (i, sv) in vectors.row_iter().enumerate() {
-            let mut sum = f32s::splat(0.0);
-            let feature: &[f32s] = &feature;
-
-            for (a, b) in sv.iter().zip(feature) {
-                sum += (*a - *b) * (*a - *b);
-            }
+            let sum = squared_distance(sv, feature, mask);
 
             // This seems to be the single-biggest CPU spike: saving back kernel_values,
             // and computing exp() (saving back seems to have 3x time impact over exp(),
             // but I might misread "Instruments" for that particular one).
-            output[i] = f64::from((-self.gamma * sum.sum()).exp());
+            output[i] = f64::from((-gamma * sum).exp());
+        }
+    }
+
+    fn compute_f64(&self, _vectors: &SimdMatrix<f32s, RowOptimized>, vectors_f64: &SimdMatrix<f64s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        for (i, _) in vectors_f64.row_iter().enumerate() {
+            output[i] = (-self.gamma * squared_distance_f64(vectors_f64.row_as_flat(i), feature.flat())).exp();
         }
     }
 }
 
 impl KernelSparse for Rbf {
     fn compute(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64]) {
+        let gamma = self.gamma as f32;
+
         for (i, sv) in vectors.row_iter().enumerate() {
             let mut sum = 0.0;
             let mut a_iter = sv.iter();
@@ -54,7 +61,7 @@ impl KernelSparse for Rbf {
                     }
                     (Some((i_a, _)), Some((i_b, _))) if i_a < i_b => a = a_iter.next(),
                     (Some((i_a, _)), Some((i_b, _))) if i_a > i_b => b = b_iter.next(),
-                    _ => break f64::from((-self.gamma * sum).exp()),
+                    _ => break f64::from((-gamma * sum).exp()),
                 }
             }
         }
@@ -70,3 +77,7 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for Rbf {
         Ok(Rbf { gamma })
     }
 }
+
+impl From<Rbf> for KernelInfo {
+    fn from(kernel: Rbf) -> Self { KernelInfo::Rbf { gamma: kernel.gamma } }
+}
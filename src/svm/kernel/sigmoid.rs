@@ -1,23 +1,25 @@
 use std::convert::{From, TryFrom};
 
-use super::{KernelDense, KernelSparse};
+use super::{dot_f64, KernelDense, KernelInfo, KernelSparse};
 use crate::{
     errors::Error,
     parser::ModelFile,
     sparse::{SparseMatrix, SparseVector},
 };
 
-use simd_aligned::{f32s, RowOptimized, SimdMatrix, SimdVector};
+use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
 
 #[derive(Copy, Clone, Debug, Default)]
 #[doc(hidden)]
 pub struct Sigmoid {
-    gamma: f32,
-    coef0: f32,
+    gamma: f64,
+    coef0: f64,
 }
 
 impl KernelDense for Sigmoid {
     fn compute(&self, vectors: &SimdMatrix<f32s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        let (gamma, coef0) = (self.gamma as f32, self.coef0 as f32);
+
         for (i, sv) in vectors.row_iter().enumerate() {
             let mut sum = f32s::splat(0.0);
             let feature: &[f32s] = &feature;
@@ -26,13 +28,23 @@ impl KernelDense for Sigmoid {
                 sum += *a * *b;
             }
 
-            output[i] = (f64::from(self.gamma * sum.sum() + self.coef0)).tanh();
+            output[i] = (f64::from(gamma * sum.sum() + coef0)).tanh();
+        }
+    }
+
+    fn compute_f64(&self, _vectors: &SimdMatrix<f32s, RowOptimized>, vectors_f64: &SimdMatrix<f64s, RowOptimized>, feature: &SimdVector<f32s>, output: &mut [f64]) {
+        let (gamma, coef0) = (self.gamma, self.coef0);
+
+        for (i, _) in vectors_f64.row_iter().enumerate() {
+            output[i] = (gamma * dot_f64(vectors_f64.row_as_flat(i), feature.flat()) + coef0).tanh();
         }
     }
 }
 
 impl KernelSparse for Sigmoid {
     fn compute(&self, vectors: &SparseMatrix<f32>, feature: &SparseVector<f32>, output: &mut [f64]) {
+        let (gamma, coef0) = (self.gamma as f32, self.coef0 as f32);
+
         for (i, sv) in vectors.row_iter().enumerate() {
             let mut sum = 0.0;
             let mut a_iter = sv.iter();
@@ -50,7 +62,7 @@ impl KernelSparse for Sigmoid {
                     }
                     (Some((i_a, _)), Some((i_b, _))) if i_a < i_b => a = a_iter.next(),
                     (Some((i_a, _)), Some((i_b, _))) if i_a > i_b => b = b_iter.next(),
-                    _ => break (f64::from(self.gamma * sum + self.coef0)).tanh(),
+                    _ => break (f64::from(gamma * sum + coef0)).tanh(),
                 }
             }
         }
@@ -67,3 +79,12 @@ impl<'a, 'b> TryFrom<&'a ModelFile<'b>> for Sigmoid {
         Ok(Sigmoid { gamma, coef0 })
     }
 }
+
+impl From<Sigmoid> for KernelInfo {
+    fn from(kernel: Sigmoid) -> Self {
+        KernelInfo::Sigmoid {
+            gamma: kernel.gamma,
+            coef0: kernel.coef0,
+        }
+    }
+}
@@ -6,11 +6,16 @@ crate mod problem;
 
 use self::kernel::{KernelDense, KernelSparse};
 use crate::{
+    errors::Error,
     sparse::{SparseMatrix, SparseVector},
     vectors::Triangular,
 };
 
 use simd_aligned::{f32s, f64s, RowOptimized, SimdMatrix, SimdVector};
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default)]
 crate struct Probabilities {
@@ -20,12 +25,128 @@ crate struct Probabilities {
 }
 
 /// Classifier type.
+///
+/// Converts to and from the `svm_type` strings used by libSVM, e.g., `"c_svc"`.
 #[doc(hidden)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SVMType {
     CSvc,
     NuSvc,
     ESvr,
     NuSvr,
+    OneClass,
+}
+
+impl SVMType {
+    /// Returns `true` for C-SVC and ν-SVC models.
+    crate fn is_classifier(self) -> bool {
+        match self {
+            SVMType::CSvc | SVMType::NuSvc => true,
+            SVMType::ESvr | SVMType::NuSvr | SVMType::OneClass => false,
+        }
+    }
+}
+
+impl fmt::Display for SVMType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SVMType::CSvc => "c_svc",
+            SVMType::NuSvc => "nu_svc",
+            SVMType::ESvr => "epsilon_svr",
+            SVMType::NuSvr => "nu_svr",
+            SVMType::OneClass => "one_class",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SVMType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SVMType, Error> {
+        match s {
+            "c_svc" => Ok(SVMType::CSvc),
+            "nu_svc" => Ok(SVMType::NuSvc),
+            "epsilon_svr" => Ok(SVMType::ESvr),
+            "nu_svr" => Ok(SVMType::NuSvr),
+            "one_class" => Ok(SVMType::OneClass),
+            unknown => Err(Error::ParsingError(format!("Unknown svm_type `{}`", unknown))),
+        }
+    }
+}
+
+/// Controls the numerical precision used when summing up decision values.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PrecisionMode {
+    /// Sums decision values SIMD lane by lane. This is the default and the fastest option.
+    Fast,
+
+    /// Like [PrecisionMode::Fast], but pairwise decision values closer than `epsilon` to
+    /// the decision boundary are recomputed with compensated summation. Since only near-ties
+    /// are recomputed this is almost as fast as [PrecisionMode::Fast]. Labels match those of
+    /// [PrecisionMode::Precise] as long as the rounding error of the fast sums stays below
+    /// `epsilon`; this is not checked, so pick `epsilon` generously.
+    Adaptive {
+        /// Absolute distance to `0.0` below which a decision value is recomputed.
+        epsilon: f64,
+    },
+
+    /// Always uses compensated summation for all decision values.
+    Precise,
+}
+
+impl Default for PrecisionMode {
+    fn default() -> Self { PrecisionMode::Fast }
+}
+
+/// Controls the floating point precision kernel values are computed with.
+///
+/// libSVM computes kernels in `f64`, while FFSVM by default uses `f32` SIMD arithmetic. For
+/// ill-conditioned models (e.g., RBF kernels with large `gamma`) the rounding errors can flip
+/// labels of samples close to the decision boundary.
+///
+/// Model parameters are parsed as `f64`. For `Double`, dense SVMs use the support vectors,
+/// `gamma` and `coef0` in that precision and only widen the `f32` features. To do so they keep
+/// an `f64` copy of all support vectors next to the `f32` one, which triples the memory used
+/// for support vectors whichever precision is selected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KernelPrecision {
+    /// Computes kernels with `f32` SIMD arithmetic. This is the default and the fastest option.
+    Single,
+
+    /// Computes kernels with scalar `f64` arithmetic, matching libSVM more closely at a
+    /// considerable speed cost. Only affects [DenseSVM]s using a built-in kernel.
+    Double,
+}
+
+impl Default for KernelPrecision {
+    fn default() -> Self { KernelPrecision::Single }
+}
+
+/// Controls when the multiclass probability solver of [Predict::predict_probability] gives up.
+///
+/// The defaults are the values libSVM uses. Models with many classes may need more iterations
+/// to converge instead of failing with [Error::IterationsExceeded].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbabilitySettings {
+    /// Maximum number of iterations, raised to the number of classes if lower. Defaults to `100`.
+    pub max_iterations: usize,
+
+    /// The solver has converged once its error falls below `epsilon / classes`. Defaults to `0.005`.
+    pub epsilon: f64,
+}
+
+impl Default for ProbabilitySettings {
+    fn default() -> Self {
+        ProbabilitySettings {
+            max_iterations: 100,
+            epsilon: 0.005,
+        }
+    }
 }
 
 /// **Start here** to classify dense models with highest performance.
@@ -33,3 +154,18 @@ pub type DenseSVM = core::SVMCore<dyn KernelDense, SimdMatrix<f32s, RowOptimized
 
 /// Use this to load any `libSVM` model with normal performance.
 pub type SparseSVM = core::SVMCore<dyn KernelSparse, SparseMatrix<f32>, SparseVector<f32>, SparseVector<f64>>;
+
+#[cfg(test)]
+mod tests {
+    use super::SVMType;
+
+    #[test]
+    fn svm_type_round_trip() {
+        for svm_type in &[SVMType::CSvc, SVMType::NuSvc, SVMType::ESvr, SVMType::NuSvr, SVMType::OneClass] {
+            assert_eq!(svm_type.to_string().parse::<SVMType>().unwrap(), *svm_type);
+        }
+
+        assert_eq!(SVMType::ESvr.to_string(), "epsilon_svr");
+        assert!("c_svr".parse::<SVMType>().is_err());
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{errors::Error, svm::problem::Problem};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 /// Implemented by [DenseSVM] and [SparseSVM] to predict a [Problem].
 ///
 /// # Predicting a label
@@ -51,5 +54,33 @@ where
     /// The problem needs to have all features set. Once this method returns,
     /// both [Problem::solution] will be set, and all [Problem::probabilities] will
     /// be available accordingly.
+    ///
+    /// Returns [Error::NoProbabilities] without touching the problem if the model was
+    /// trained without probability estimates (`svm-train -b 1`).
     fn predict_probability(&self, problem: &mut Problem<V32>) -> Result<(), Error>;
+
+    /// Predicts a value for each [Problem] of a slice.
+    ///
+    /// The result is identical to calling [Predict::predict_value] on each problem
+    /// individually; the first error encountered is returned and remaining problems are left
+    /// untouched.
+    fn predict_batch(&self, problems: &mut [Problem<V32>]) -> Result<(), Error> {
+        for problem in problems.iter_mut() {
+            self.predict_value(problem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Predicts a value for each [Problem] of a slice, distributing them across all cores.
+    ///
+    /// Behaves like [Predict::predict_batch], but problems are classified in parallel
+    /// using `rayon`. Requires the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    fn predict_batch_parallel(&self, problems: &mut [Problem<V32>]) -> Result<(), Error>
+    where
+        Problem<V32>: Send,
+    {
+        problems.par_iter_mut().try_for_each(|problem| self.predict_value(problem))
+    }
 }
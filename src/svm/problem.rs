@@ -1,8 +1,13 @@
-use std::ops::{Index, IndexMut};
+use std::{
+    cmp::Ordering,
+    ops::{Index, IndexMut},
+};
 
 use crate::{
+    errors::Error,
     sparse::SparseVector,
-    svm::{DenseSVM, SparseSVM},
+    svm::{kernel::KernelInfo, DenseSVM, SparseSVM},
+    util::set_all,
     vectors::Triangular,
 };
 
@@ -22,15 +27,29 @@ pub type SparseProblem = Problem<SparseVector<f32>>;
 #[derive(Copy, Debug, Clone, PartialEq)]
 pub enum Solution {
     /// If classified this will hold the label.
-    Label(u32),
+    Label(i32),
 
     /// If regression was performed contains regression result.
     Value(f32),
 
+    /// For one-class SVMs, `true` if the problem lies inside the learned region (libSVM's `+1`),
+    /// `false` if it is an outlier (libSVM's `-1`).
+    Inlier(bool),
+
     /// No operation was performed yet.
     None,
 }
 
+/// How the label of a [Solution::Label] was chosen, see [Problem::label_source].
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum LabelSource {
+    /// The class with the most pairwise votes won, as by [Predict::predict_value].
+    Votes,
+
+    /// The class with the highest probability won, as by [Predict::predict_probability].
+    Probabilities,
+}
+
 #[derive(Debug, Clone)]
 pub struct Features<V32> {
     data: V32,
@@ -103,20 +122,346 @@ pub struct Problem<V32> {
 
     /// Computed label that will be updated after this problem was processed.
     crate result: Solution,
+
+    /// How `result` was chosen if it is a label.
+    crate label_source: Option<LabelSource>,
+
+    /// Full precision regression result, only set for ε-SVR and ν-SVR models.
+    crate regression_value: Option<f64>,
+
+    /// Scale of the Laplace distributed regression noise (libSVM's `probA`), only set by
+    /// `predict_probability` for regression models trained with probability estimates.
+    crate regression_sigma: Option<f64>,
+
+    /// Number of iterations the multiclass probability solver needed.
+    crate probability_iterations: usize,
+
+    /// Temporary storage for kernels, see `KernelDense::compute_with_scratch`. Parallel kernel
+    /// computation uses it to hold the kernel values of all classes in one flat buffer instead.
+    crate kernel_scratch: Vec<f64>,
+
+    /// Number of attributes of the SVM this problem was created for.
+    crate num_attributes: usize,
+
+    /// Number of support vectors of the SVM this problem was created for.
+    crate num_total_sv: usize,
 }
 
 impl<T> Problem<T> {
     /// After a [Problem] has been classified, this will hold the SVMs solution.
     pub fn solution(&self) -> Solution { self.result }
 
+    /// After a [Problem] has been predicted by a regression model (ε-SVR or ν-SVR), returns
+    /// the regression result in full `f64` precision. Returns `None` for classification models
+    /// or problems not predicted yet.
+    pub fn regression_value(&self) -> Option<f64> { self.regression_value }
+
+    /// After a [Problem] has been predicted via [Predict::predict_probability] by a regression
+    /// model trained with probability estimates (`svm-train -s 3 -b 1`), returns an interval
+    /// `(lower, upper)` around [Problem::regression_value] that holds the true value with
+    /// probability `p`.
+    ///
+    /// Like libSVM, this assumes the prediction error is Laplace distributed with scale `sigma`
+    /// (the model's `probA`), so the interval is `value ± -sigma * ln(1 - p)`.
+    ///
+    /// Returns `None` unless such a prediction was made, or if `p` is not in `[0, 1)`.
+    pub fn regression_confidence_interval(&self, p: f64) -> Option<(f64, f64)> {
+        if !(p >= 0.0 && p < 1.0) {
+            return None;
+        }
+
+        let value = self.regression_value?;
+        let half_width = -self.regression_sigma? * (1.0 - p).ln();
+
+        Some((value - half_width, value + half_width))
+    }
+
+    /// After a [Problem] has been predicted by a one-class SVM, returns if it lies inside the
+    /// learned region. Returns `None` for other models or problems not predicted yet.
+    pub fn is_inlier(&self) -> Option<bool> {
+        match self.result {
+            Solution::Inlier(inlier) => Some(inlier),
+            _ => None,
+        }
+    }
+
     /// Returns the probability estimates. Only really useful if the model was trained with probability estimates and you classified with them.
     pub fn probabilities(&self) -> &[f64] { self.probabilities.flat() }
 
+    /// After a [Problem] has been classified, returns the decision value between class index
+    /// `class_a` and `class_b`.
+    ///
+    /// Positive values favor `class_a`, negative ones `class_b`, so swapping both arguments
+    /// negates the result. Returns `None` if `class_a == class_b` or either index is out of range.
+    pub fn decision_value(&self, class_a: usize, class_b: usize) -> Option<f64> {
+        let dimension = self.decision_values.dimension;
+
+        if class_a >= dimension || class_b >= dimension {
+            return None;
+        }
+
+        match class_a.cmp(&class_b) {
+            Ordering::Less => Some(self.decision_values[(class_a, class_b)]),
+            Ordering::Greater => Some(-self.decision_values[(class_b, class_a)]),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// After a [Problem] has been classified, returns all class index pairs `(i, j)` with `i < j`
+    /// together with their decision value, ordered by `i` and then `j`.
+    ///
+    /// Values are the continuous sums before they are turned into votes, with the same sign
+    /// convention as [Problem::decision_value].
+    pub fn decision_values_iter(&self) -> impl Iterator<Item = ((usize, usize), f64)> + '_ {
+        let dimension = self.decision_values.dimension;
+
+        (0 .. dimension).flat_map(move |i| (i + 1 .. dimension).map(move |j| ((i, j), self.decision_values[(i, j)])))
+    }
+
+    /// After a [Problem] has been classified, returns how many pairwise decisions were won by
+    /// class index `label_index`, see [SVMCore::class_index_for_label].
+    ///
+    /// # Panics
+    ///
+    /// If `label_index` is not a valid class index.
+    pub fn vote_count(&self, label_index: usize) -> u32 { self.vote[label_index] }
+
+    /// After a [Problem] has been classified, returns by how many votes the winning class beat
+    /// the runner-up.
+    ///
+    /// A margin of `0` means two classes tied and the label was picked by class order, small
+    /// margins indicate a low-confidence prediction.
+    pub fn winning_margin(&self) -> u32 {
+        let (mut first, mut second) = (0, 0);
+
+        for &votes in &self.vote {
+            if votes > first {
+                second = first;
+                first = votes;
+            } else if votes > second {
+                second = votes;
+            }
+        }
+
+        first - second
+    }
+
+    /// After a [Problem] has been classified, returns how its label was chosen, or `None` if there
+    /// is no label, e.g., for regression and one-class models.
+    pub fn label_source(&self) -> Option<LabelSource> { self.label_source }
+
+    /// After a [Problem] has been classified, returns if two or more classes received the most votes.
+    ///
+    /// # Description
+    ///
+    /// Ties are broken the same way libSVM does: each pairwise decision value of exactly `0.0`
+    /// votes for the class with the higher index, and among classes with equally many votes the
+    /// one with the lowest class index (see [SVMCore::class_index_for_label]) wins. Labels are
+    /// therefore deterministic, but a tie means the prediction is ambiguous.
+    ///
+    /// Always `false` unless the label was picked by votes, i.e., for problems that were not
+    /// classified yet, for regression and one-class models, and after [Predict::predict_probability].
+    pub fn is_tie(&self) -> bool {
+        if self.label_source != Some(LabelSource::Votes) {
+            return false;
+        }
+
+        let most = self.vote.iter().max().cloned().unwrap_or(0);
+
+        self.vote.iter().filter(|votes| **votes == most).count() > 1
+    }
+
+    /// After a [Problem] has been predicted via [Predict::predict_probability], returns how many
+    /// iterations the multiclass probability solver needed to converge, at least `1`. If it gave
+    /// up with [Error::IterationsExceeded], returns the maximum number of iterations it ran
+    /// instead. Models with two classes do not need the solver and report `0`.
+    pub fn probability_iterations(&self) -> usize { self.probability_iterations }
+
     /// Returns the features. You must set them first and classifiy the problem before you can get a solution.
     pub fn features(&mut self) -> &mut Features<T> { &mut self.features }
+
+    /// Returns the kernel values computed during the last prediction.
+    ///
+    /// Row `i` holds the kernel values of all support vectors of class index `i`, in the order
+    /// they appear in the model file.
+    pub fn kernel_values(&self) -> &SimdMatrix<f64s, RowOptimized> { &self.kernel_values }
+
+    /// Returns the kernel values for modification, for example to supply externally computed
+    /// kernel values to [SVMCore::predict_from_kernel_values]. See [Problem::kernel_values] for
+    /// the layout.
+    pub fn kernel_values_mut(&mut self) -> &mut SimdMatrix<f64s, RowOptimized> { &mut self.kernel_values }
+
+    /// Resets all computed state (votes, kernel values, pairwise probabilities, decision values,
+    /// probabilities and the solution) while keeping all allocations, so a single [Problem] can
+    /// be reused for many predictions.
+    ///
+    /// The features are not touched and should be overwritten before calling
+    /// [Predict::predict_value] again. For [SparseProblem]s also see [SparseProblem::clear].
+    pub fn reset(&mut self) {
+        let num_classes = self.vote.len();
+
+        for i in 0 .. num_classes {
+            set_all(self.kernel_values.row_as_flat_mut(i), 0.0);
+            set_all(self.pairwise.row_as_flat_mut(i), 0.0);
+            set_all(self.q.row_as_flat_mut(i), 0.0);
+        }
+
+        set_all(&mut self.vote, 0);
+        set_all(&mut self.qp, 0.0);
+        set_all(&mut self.decision_values.data, 0.0);
+        set_all(self.probabilities.flat_mut(), 0.0);
+
+        self.result = Solution::None;
+        self.label_source = None;
+        self.regression_value = None;
+        self.regression_sigma = None;
+        self.probability_iterations = 0;
+    }
 }
 
 impl DenseProblem {
+    /// Creates a new problem for the given SVM with all features set in one call.
+    ///
+    /// Returns [Error::DimensionMismatch] if `features` does not have one value per attribute.
+    pub fn from_features(svm: &DenseSVM, features: &[f32]) -> Result<Self, Error> {
+        if features.len() != svm.num_attributes {
+            return Err(Error::DimensionMismatch {
+                expected: svm.num_attributes,
+                actual: features.len(),
+            });
+        }
+
+        let mut problem = Problem::from(svm);
+        problem.features.data.flat_mut()[.. features.len()].copy_from_slice(features);
+
+        Ok(problem)
+    }
+
+    /// Checks that all features are finite.
+    ///
+    /// `NaN` or infinite features (e.g., from a division by zero upstream) propagate into the
+    /// kernel values and silently produce meaningless predictions. Call this before predicting
+    /// untrusted input to fail loudly instead.
+    ///
+    /// Returns [Error::NonFiniteFeature] for the first non-finite feature.
+    pub fn validate_finite(&self) -> Result<(), Error> {
+        match self.features.data.flat().iter().position(|x| !x.is_finite()) {
+            Some(index) => Err(Error::NonFiniteFeature {
+                index,
+                value: self.features.data.flat()[index],
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets feature `index` to `value`.
+    ///
+    /// Returns [Error::InvalidFeatureIndex] instead of panicking if `index` is not below
+    /// the number of attributes of the SVM this problem was created for.
+    pub fn set_feature(&mut self, index: usize, value: f32) -> Result<(), Error> {
+        self.check_feature_index(index)?;
+        self.features.data.flat_mut()[index] = value;
+
+        Ok(())
+    }
+
+    /// Sets all features from `(index, value)` pairs, e.g., from sparse upstream data. All
+    /// features not given are set to `0.0`.
+    ///
+    /// Returns [Error::InvalidFeatureIndex] for the first invalid index, in which case no
+    /// feature is changed.
+    pub fn set_features_sparse(&mut self, pairs: &[(usize, f32)]) -> Result<(), Error> {
+        for (index, _) in pairs {
+            self.check_feature_index(*index)?;
+        }
+
+        let features = self.features.data.flat_mut();
+
+        set_all(features, 0.0);
+
+        for (index, value) in pairs {
+            features[*index] = *value;
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure `index` denotes an attribute of the SVM this problem was created for.
+    fn check_feature_index(&self, index: usize) -> Result<(), Error> {
+        if index >= self.num_attributes {
+            return Err(Error::InvalidFeatureIndex {
+                index,
+                attributes: self.num_attributes,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// After this problem was predicted by `svm` via [Predict::predict_probability], returns the
+    /// `k` most probable classes as `(label, probability)` pairs, most probable first.
+    ///
+    /// Returns fewer pairs if the model has less than `k` classes, or [Error::NoProbabilities]
+    /// if the model was trained without probability estimates.
+    pub fn top_k(&self, svm: &DenseSVM, k: usize) -> Result<Vec<(i32, f64)>, Error> {
+        let mut labeled = svm.labeled_probabilities(self)?;
+
+        // Stable, so ties stay in class index order.
+        labeled.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        labeled.truncate(k);
+
+        Ok(labeled)
+    }
+
+    /// After this problem was predicted by `svm`, estimates how much each feature influenced
+    /// the prediction of an RBF model.
+    ///
+    /// # Description
+    ///
+    /// The RBF kernel value of a support vector is `exp(-gamma * sum_i (x_i - sv_i)^2)`, so
+    /// every feature `i` contributes `gamma * (x_i - sv_i)^2` to the exponent. Support vectors
+    /// far away from the problem have kernel values close to `0.0` and hardly matter. Therefore
+    /// each support vector's per-feature contributions are weighted by its kernel value times
+    /// the sum of its absolute coefficients, and accumulated over all support vectors.
+    ///
+    /// Large values mean the feature pulled the problem away from the support vectors that
+    /// shaped the prediction. This is a crude diagnostic, not a proper attribution method.
+    ///
+    /// # Returns
+    ///
+    /// One value per attribute, [Error::NotRbf] unless `svm` uses an RBF kernel, or
+    /// [Error::DimensionMismatch] if this problem was not created for `svm`.
+    pub fn feature_contributions(&self, svm: &DenseSVM) -> Result<Vec<f64>, Error> {
+        let gamma = match svm.kernel_info {
+            KernelInfo::Rbf { gamma } => gamma,
+            _ => return Err(Error::NotRbf),
+        };
+
+        svm.check_dimensions(self)?;
+
+        let num_coefficients = svm.classes.len().max(2) - 1;
+        let features = self.features.data.flat();
+        let kernel_values = self.kernel_values.flat();
+        let mut contributions = vec![0.0; svm.num_attributes];
+
+        for (i_class, class) in svm.classes.iter().enumerate() {
+            let coefficients = class.coefficients.flat();
+            let support_vectors = class.support_vectors.flat();
+
+            for i_vector in 0 .. class.num_support_vectors {
+                let coefficient_sum = (0 .. num_coefficients).map(|i_coefficient| coefficients[(i_coefficient, i_vector)].abs()).sum::<f64>();
+                let weight = kernel_values[(i_class, i_vector)] * coefficient_sum * gamma;
+
+                for (i_attribute, contribution) in contributions.iter_mut().enumerate() {
+                    let distance = f64::from(features[i_attribute]) - f64::from(support_vectors[(i_vector, i_attribute)]);
+                    *contribution += weight * distance * distance;
+                }
+            }
+        }
+
+        Ok(contributions)
+    }
+
     /// Creates a new problem with the given parameters.
     crate fn with_dimension(total_sv: usize, num_classes: usize, num_attributes: usize) -> Problem<SimdVector<f32s>> {
         Problem {
@@ -131,6 +476,13 @@ impl DenseProblem {
             vote: vec![Default::default(); num_classes],
             probabilities: SimdVector::with(0.0, num_classes),
             result: Solution::None,
+            regression_value: None,
+            regression_sigma: None,
+            label_source: None,
+            probability_iterations: 0,
+            kernel_scratch: Vec::new(),
+            num_attributes,
+            num_total_sv: total_sv,
         }
     }
 }
@@ -139,8 +491,18 @@ impl SparseProblem {
     /// Clears the [Problem] when reusing it between calls. Only needed for [SparseSVM] problems.
     pub fn clear(&mut self) { self.features.data.clear(); }
 
+    /// Checks that all features are finite, see [DenseProblem::validate_finite].
+    ///
+    /// Returns [Error::NonFiniteFeature] for the first non-finite feature.
+    pub fn validate_finite(&self) -> Result<(), Error> {
+        match self.features.data.iter().find(|(_, x)| !x.is_finite()) {
+            Some((index, value)) => Err(Error::NonFiniteFeature { index: index as usize, value }),
+            None => Ok(()),
+        }
+    }
+
     /// Creates a new problem with the given parameters.
-    crate fn with_dimension(total_sv: usize, num_classes: usize, _num_attributes: usize) -> Problem<SparseVector<f32>> {
+    crate fn with_dimension(total_sv: usize, num_classes: usize, num_attributes: usize) -> Problem<SparseVector<f32>> {
         Problem {
             features: Features { data: SparseVector::new() },
             kernel_values: SimdMatrix::with_dimension(num_classes, total_sv),
@@ -151,6 +513,13 @@ impl SparseProblem {
             vote: vec![Default::default(); num_classes],
             probabilities: SimdVector::with(0.0, num_classes),
             result: Solution::None,
+            regression_value: None,
+            regression_sigma: None,
+            label_source: None,
+            probability_iterations: 0,
+            kernel_scratch: Vec::new(),
+            num_attributes,
+            num_total_sv: total_sv,
         }
     }
 }
@@ -168,7 +537,19 @@ impl<V32> Features<V32> {
 }
 
 impl Features<SimdVector<f32s>> {
+    /// Returns all features as a mutable slice of [SVMCore::attributes] values, without SIMD padding.
     pub fn as_slice_mut(&mut self) -> &mut [f32] { self.data.flat_mut() }
+
+    /// Computes the dot product with a weight vector.
+    crate fn dot(&self, weights: &[f64]) -> f64 { self.data.flat().iter().zip(weights).map(|(x, w)| f64::from(*x) * w).sum() }
+}
+
+impl Features<SparseVector<f32>> {
+    /// Computes the dot product with a weight vector.
+    crate fn dot(&self, weights: &[f64]) -> f64 {
+        // Features beyond the model's attributes can't contribute.
+        self.data.iter().map(|(index, x)| f64::from(x) * weights.get(index as usize).unwrap_or(&0.0)).sum()
+    }
 }
 
 impl Index<usize> for Features<SimdVector<f32s>> // where
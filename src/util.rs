@@ -10,7 +10,7 @@ where
     }
 }
 
-/// Finds the item with the maximum index.
+/// Finds the index of the largest item. On ties the lowest index wins, like in libSVM.
 pub fn find_max_index<T>(array: &[T]) -> usize
 where
     T: PartialOrd,
@@ -26,6 +26,27 @@ where
     vote_max_idx
 }
 
+/// Computes the dot product of two slices using Neumaier's variant of Kahan summation.
+pub fn compensated_dot(a: &[f64], b: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+
+    for (x, y) in a.iter().zip(b) {
+        let value = x * y;
+        let t = sum + value;
+
+        if f64::abs(sum) >= f64::abs(value) {
+            compensation += (sum - t) + value;
+        } else {
+            compensation += (value - t) + sum;
+        }
+
+        sum = t;
+    }
+
+    sum + compensation
+}
+
 /// As implemented in `libsvm`.
 pub fn sigmoid_predict(decision_value: f64, a: f64, b: f64) -> f64 {
     let fapb = decision_value * a + b;
@@ -0,0 +1,68 @@
+#![feature(try_from)]
+
+use ffsvm::{CompositeKernel, DenseSVM, Error, KernelDense, Linear, Predict, Problem, Rbf};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    convert::TryFrom,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Forwards to the system allocator, counting all allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) { System.dealloc(ptr, layout) }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns how many allocations happened meanwhile.
+fn count_allocations<F>(f: F) -> Result<usize, Error>
+where
+    F: FnOnce() -> Result<(), Error>,
+{
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    f()?;
+    Ok(ALLOCATIONS.load(Ordering::SeqCst) - before)
+}
+
+// This is the only test in this binary, so no other thread allocates meanwhile.
+#[test]
+fn reused_problem_predicts_without_allocating() -> Result<(), Error> {
+    let mut svm = DenseSVM::try_from(include_str!("data_dense/m_csvm_linear_prob.libsvm"))?;
+    let mut problem = Problem::from(&svm);
+
+    problem.features().as_slice_mut().clone_from_slice(&[1.28, 0.98, 1.48, 1.12, 0.89, 1.16, 0.92, 1.14]);
+
+    assert_eq!(count_allocations(|| svm.predict_value(&mut problem))?, 0);
+    assert_eq!(count_allocations(|| svm.predict_probability(&mut problem))?, 0);
+
+    svm.enable_linear_fast()?;
+
+    assert_eq!(count_allocations(|| svm.predict_value(&mut problem))?, 0);
+    assert_eq!(count_allocations(|| svm.predict_probability(&mut problem))?, 0);
+
+    // Composite kernels keep their scratch buffer in the problem, only the first prediction allocates it.
+    let composite = CompositeKernel::<dyn KernelDense>::new()
+        .with_kernel(0.5, Box::new(Linear::default()))
+        .with_kernel(0.5, Box::new(Rbf { gamma: 0.5 }));
+    let svm = svm.with_replaced_kernel(Box::new(composite));
+    let mut problem = Problem::from(&svm);
+
+    problem.features().as_slice_mut().clone_from_slice(&[1.28, 0.98, 1.48, 1.12, 0.89, 1.16, 0.92, 1.14]);
+
+    svm.predict_value(&mut problem)?;
+
+    assert_eq!(count_allocations(|| svm.predict_value(&mut problem))?, 0);
+    assert_eq!(count_allocations(|| svm.predict_probability(&mut problem))?, 0);
+
+    Ok(())
+}
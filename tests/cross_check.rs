@@ -0,0 +1,68 @@
+#![feature(try_from)]
+#![cfg(feature = "testing")]
+
+use ffsvm::{cross_check_representations, DenseSVM, Error, ModelFile};
+use std::{convert::TryFrom, fs};
+
+/// Rewrites a model with 0-based attribute indices into the 1-based form `svm-train` writes for regular libSVM data.
+fn one_based(model: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_vectors = false;
+
+    for line in model.lines() {
+        if !in_vectors {
+            in_vectors = line.trim() == "SV";
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let shifted = line
+            .split_whitespace()
+            .map(|token| match token.find(':') {
+                Some(colon) => format!("{}:{}", token[.. colon].parse::<u32>().unwrap() + 1, &token[colon + 1 ..]),
+                None => token.to_string(),
+            }).collect::<Vec<_>>();
+
+        lines.push(shifted.join(" "));
+    }
+
+    lines.join("\n")
+}
+
+/// Checks that dense and sparse predictions of `text` agree.
+fn check_agreement(name: &str, text: &str) -> Result<(), Error> {
+    let model = ModelFile::try_from(text)?;
+    let attributes = DenseSVM::try_from_indexed(text)?.attributes();
+
+    // Deterministic inputs with every third feature zero, so the sparse problems have gaps.
+    let inputs = (0 .. 50)
+        .map(|k| {
+            (0 .. attributes)
+                .map(|a| if (k + a) % 3 == 0 { 0.0 } else { ((k * 7 + a * 13) % 17) as f32 / 17.0 })
+                .collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+    let diverging = cross_check_representations(&model, &inputs, 1e-3)?;
+
+    assert!(diverging.is_empty(), "{} diverges for inputs {:?}", name, diverging);
+
+    Ok(())
+}
+
+#[test]
+fn dense_and_sparse_agree_on_test_models() -> Result<(), Error> {
+    for entry in fs::read_dir("tests/data_dense")? {
+        let path = entry?.path();
+
+        if path.extension().map_or(true, |extension| extension != "libsvm") {
+            continue;
+        }
+
+        let text = fs::read_to_string(&path)?;
+
+        check_agreement(&format!("{:?}", path), &text)?;
+        check_agreement(&format!("{:?} (1-based)", path), &one_based(&text))?;
+    }
+
+    Ok(())
+}
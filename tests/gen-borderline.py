@@ -0,0 +1,46 @@
+#!/usr/bin/python
+
+# Prints the borderline RBF model used by the `kernel_precision_borderline_rbf` test, together
+# with the decision value and label libSVM predicts for it.
+#
+# The decision value follows `svm_predict_values` in libSVM's `svm.cpp`: `k_function` for RBF
+# sums `(x - y)^2` over the attributes in `double` and returns `exp(-gamma * sum)`, the decision
+# value is the sum of `coef * k` over the support vectors of both classes minus `rho`.
+#
+# Both support vectors round to the same `f32`, so in single precision their kernel values
+# cancel and the decision value is `-rho`, which flips the label.
+
+import math
+import struct
+
+GAMMA = 0.5
+RHO = 0.9e-9
+SUPPORT_VECTORS = [(1.0, [0.30000002, 0.6, 0.9]), (-1.0, [0.30000001, 0.6, 0.9])]
+FEATURES = [0.5, 0.25, 0.75]
+
+
+def f32(x): return struct.unpack("<f", struct.pack("<f", x))[0]
+
+
+def k_function(x, y):
+    sum = 0.0
+    for a, b in zip(x, y):
+        d = a - b
+        sum += d * d
+    return math.exp(-GAMMA * sum)
+
+
+def model():
+    lines = ["svm_type c_svc", "kernel_type rbf", "gamma %r" % GAMMA, "nr_class 2", "total_sv 2", "rho %r" % RHO, "label 1 -1", "nr_sv 1 1", "SV"]
+    for coef, sv in SUPPORT_VECTORS:
+        lines.append("%r " % coef + " ".join("%d:%r" % (i + 1, v) for i, v in enumerate(sv)))
+    return "\n".join(lines) + "\n"
+
+
+assert all(f32(x) == x for x in FEATURES)
+assert f32(SUPPORT_VECTORS[0][1][0]) == f32(SUPPORT_VECTORS[1][1][0])
+
+decision_value = sum(coef * k_function(FEATURES, sv) for coef, sv in SUPPORT_VECTORS) - RHO
+
+print(model())
+print("decision value %r, label %d" % (decision_value, 1 if decision_value > 0 else -1))
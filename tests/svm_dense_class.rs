@@ -87,3 +87,236 @@ mod svm_dense_class {
     // test_model!(m_nusvm_rbf, "m_nusvm_rbf.libsvm", false, [0, 7], []);
     // test_model!(m_nusvm_sigmoid, "m_nusvm_sigmoid.libsvm", false, [0, 7], []);
 }
+
+#[cfg(test)]
+mod svm_dense_votes {
+    use ffsvm::{DenseSVM, Error, ModelFile, Problem, Solution};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn votes_sum_to_pairwise_decisions() -> Result<(), Error> {
+        let model = include_str!("data_dense/m_csvm_linear_prob.libsvm");
+        let svm = DenseSVM::try_from(model)?;
+
+        let mut problem = Problem::from(&svm);
+        problem.features().as_slice_mut().clone_from_slice(&[1.28, 0.98, 1.48, 1.12, 0.89, 1.16, 0.92, 1.14]);
+
+        let (label, votes) = svm.predict_votes(&mut problem)?;
+        let n = svm.classes() as u32;
+
+        assert_eq!(votes.len(), svm.classes());
+        assert_eq!(votes.iter().sum::<u32>(), n * (n - 1) / 2);
+        assert_eq!(problem.solution(), Solution::Label(label));
+
+        Ok(())
+    }
+
+    #[test]
+    fn votes_of_three_classes() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(&ModelFile::random_dense_multiclass("c_svc", "rbf", 3, 8, 8))?;
+
+        let mut problem = Problem::from(&svm);
+        problem.features().as_slice_mut().clone_from_slice(&[1.28, 0.98, 1.48, 1.12, 0.89, 1.16, 0.92, 1.14]);
+
+        let (label, votes) = svm.predict_votes(&mut problem)?;
+
+        assert_eq!(votes.len(), 3);
+        assert_eq!(votes.iter().sum::<u32>(), 3);
+        assert_eq!(problem.solution(), Solution::Label(label));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod svm_dense_precision {
+    use ffsvm::{DenseSVM, Error, Predict, PrecisionMode, Problem};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn adaptive_matches_precise_labels() -> Result<(), Error> {
+        let model = include_str!("data_dense/m_csvm_rbf_prob.libsvm");
+        let mut precise = DenseSVM::try_from(model)?;
+        let mut adaptive = DenseSVM::try_from(model)?;
+
+        precise.set_precision_mode(PrecisionMode::Precise);
+        adaptive.set_precision_mode(PrecisionMode::Adaptive { epsilon: 1e-3 });
+
+        let mut problem_precise = Problem::from(&precise);
+        let mut problem_adaptive = Problem::from(&adaptive);
+
+        // Walk along the diagonal through all class regions, crossing many boundaries.
+        for step in 0 .. 200 {
+            let x = step as f32 / 100.0;
+
+            for problem in [&mut problem_precise, &mut problem_adaptive].iter_mut() {
+                for feature in problem.features().as_slice_mut().iter_mut() {
+                    *feature = x;
+                }
+            }
+
+            precise.predict_value(&mut problem_precise)?;
+            adaptive.predict_value(&mut problem_adaptive)?;
+
+            assert_eq!(problem_precise.solution(), problem_adaptive.solution());
+        }
+
+        Ok(())
+    }
+
+    /// Class 0 contributes 2048 products of 2^48, then 1023 products of 1, then 1024 of -2^49.
+    /// All of them are exact in f64, and so is the running sum except for the ones, which
+    /// plain summation loses next to 2^59. The exact decision value is 1023.
+    fn cancellation_model() -> String {
+        let mut model = String::from("svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 4096\nrho 0\nlabel 0 1\nnr_sv 4095 1\nSV\n");
+
+        model.push_str(&"16777216 0:16777216\n".repeat(2048));
+        model.push_str(&"1 0:1\n".repeat(1023));
+        model.push_str(&"-16777216 0:33554432\n".repeat(1024));
+        model.push_str("0 0:0\n");
+
+        model
+    }
+
+    /// Predicts the cancellation model at `x = 1` and returns its only decision value.
+    fn cancellation_decision_value(precision: PrecisionMode) -> Result<f64, Error> {
+        let model = cancellation_model();
+        let mut svm = DenseSVM::try_from(model.as_str())?;
+
+        svm.set_precision_mode(precision);
+
+        let mut problem = Problem::from(&svm);
+
+        problem.features()[0] = 1.0;
+        svm.predict_value(&mut problem)?;
+
+        Ok(problem.decision_value(0, 1).unwrap())
+    }
+
+    #[test]
+    fn precise_matches_exact_sum_for_4096_svs() -> Result<(), Error> {
+        let fast = cancellation_decision_value(PrecisionMode::Fast)?;
+        let precise = cancellation_decision_value(PrecisionMode::Precise)?;
+
+        assert_eq!(precise, 1023.0);
+        assert!((fast - 1023.0).abs() > 1.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn adaptive_recomputes_inside_epsilon_band() -> Result<(), Error> {
+        let fast = cancellation_decision_value(PrecisionMode::Fast)?;
+
+        // The plain sum lies inside the band, so it is recomputed with compensated summation ...
+        let inside = cancellation_decision_value(PrecisionMode::Adaptive { epsilon: fast.abs() + 1.0 })?;
+
+        // ... but outside of it the plain sum is kept as is.
+        let outside = cancellation_decision_value(PrecisionMode::Adaptive { epsilon: fast.abs() / 2.0 })?;
+
+        assert_eq!(inside, 1023.0);
+        assert_eq!(outside, fast);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod svm_dense_nu_svc {
+    use ffsvm::{DenseSVM, Error, Predict, Problem, Solution};
+    use std::convert::TryFrom;
+
+    // With a linear kernel and one support vector per class, libSVM's one-vs-one decision
+    // values are d(1,2) = x0 - x1, d(1,3) = 2 x0 + x1, and d(2,3) = x0 + 2 x1.
+    const MODEL: &str = "svm_type nu_svc
+kernel_type linear
+nr_class 3
+total_sv 3
+rho 0 0 0
+label 1 2 3
+nr_sv 1 1 1
+SV
+1 1 0:1 1:0
+-1 1 0:0 1:1
+-1 -1 0:-1 1:-1
+";
+
+    #[test]
+    fn three_class_nu_svc() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        for (x0, x1, label) in [(2.0, 0.0, 1), (0.0, 2.0, 2), (-2.0, -2.0, 3)].iter() {
+            problem.features()[0] = *x0;
+            problem.features()[1] = *x1;
+
+            svm.predict_value(&mut problem)?;
+
+            assert_eq!(problem.solution(), Solution::Label(*label));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod svm_dense_batch {
+    use ffsvm::{DenseSVM, Error, Predict, Problem};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn batch_matches_individual_predictions() -> Result<(), Error> {
+        let model = include_str!("data_dense/m_csvm_rbf_prob.libsvm");
+        let svm = DenseSVM::try_from(model)?;
+
+        let mut batch = (0 .. 50).map(|_| Problem::from(&svm)).collect::<Vec<_>>();
+        let mut single = (0 .. 50).map(|_| Problem::from(&svm)).collect::<Vec<_>>();
+
+        for (i, problem) in batch.iter_mut().chain(single.iter_mut()).enumerate() {
+            let x = (i % 50) as f32 / 25.0;
+
+            for feature in problem.features().as_slice_mut().iter_mut() {
+                *feature = x;
+            }
+        }
+
+        svm.predict_batch(&mut batch)?;
+
+        for problem in single.iter_mut() {
+            svm.predict_value(problem)?;
+        }
+
+        for (a, b) in batch.iter().zip(single.iter()) {
+            assert_eq!(a.solution(), b.solution());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_batch_matches_serial_batch() -> Result<(), Error> {
+        let model = include_str!("data_dense/m_csvm_rbf_prob.libsvm");
+        let svm = DenseSVM::try_from(model)?;
+
+        let mut serial = (0 .. 200).map(|_| Problem::from(&svm)).collect::<Vec<_>>();
+        let mut parallel = (0 .. 200).map(|_| Problem::from(&svm)).collect::<Vec<_>>();
+
+        for (i, problem) in serial.iter_mut().chain(parallel.iter_mut()).enumerate() {
+            let x = (i % 200) as f32 / 100.0;
+
+            for feature in problem.features().as_slice_mut().iter_mut() {
+                *feature = x;
+            }
+        }
+
+        svm.predict_batch(&mut serial)?;
+        svm.predict_batch_parallel(&mut parallel)?;
+
+        for (a, b) in serial.iter().zip(parallel.iter()) {
+            assert_eq!(a.solution(), b.solution());
+        }
+
+        Ok(())
+    }
+}
@@ -93,3 +93,104 @@ mod svm_dense_regression {
     test_model!(m_nu_svr_sigmoid, "m_nu_svr_sigmoid.libsvm", false, [0.396866, 5.52985], []);
 
 }
+
+#[cfg(test)]
+mod svm_dense_regression_value {
+    use ffsvm::{DenseSVM, Error, Predict, Problem};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn epsilon_svr_regression_value() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(include_str!("data_dense/m_e_svr_rbf.libsvm"))?;
+        let mut problem = Problem::from(&svm);
+
+        assert_eq!(problem.regression_value(), None);
+
+        problem.features().as_slice_mut().clone_from_slice(&[
+            1.2877848951077797,
+            0.9860317088181307,
+            1.4862471751386734,
+            1.1280829602674647,
+            0.8910305675176804,
+            1.1643633497666765,
+            0.9285991400016091,
+            1.1407629818262937,
+        ]);
+
+        svm.predict_value(&mut problem)?;
+
+        let value = problem.regression_value().unwrap();
+        assert!((value - 6.39637).abs() < 0.001 * 6.39637);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nu_svr_regression_value() -> Result<(), Error> {
+        // Small enough to evaluate libSVM's `sum(coef * <sv, x>) - rho` by hand:
+        // 0.5 * 3 - 0.25 * 2 - 0.5 = 0.5
+        let model = "svm_type nu_svr\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0.5\nSV\n0.5 0:1 1:2\n-0.25 0:2 1:0\n";
+        let svm = DenseSVM::try_from(model)?;
+        let mut problem = Problem::from(&svm);
+
+        problem.features().as_slice_mut().clone_from_slice(&[1.0, 1.0]);
+        svm.predict_value(&mut problem)?;
+
+        assert!((problem.regression_value().unwrap() - 0.5).abs() < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn classifiers_have_no_regression_value() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(ffsvm::SAMPLE_MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        svm.predict_value(&mut problem)?;
+
+        assert_eq!(problem.regression_value(), None);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod svm_dense_one_class {
+    use ffsvm::{DenseSVM, Error, Predict, Problem, Solution};
+    use std::convert::TryFrom;
+
+    // Two support vectors at (0, 0) and (1, 1), each with coefficient 0.5 and an RBF kernel.
+    const MODEL: &str = "svm_type one_class
+kernel_type rbf
+gamma 1
+nr_class 2
+total_sv 2
+rho 0.5
+SV
+0.5 0:0 1:0
+0.5 0:1 1:1
+";
+
+    #[test]
+    fn detects_outliers() -> Result<(), Error> {
+        let svm = DenseSVM::try_from(MODEL)?;
+        let mut problem = Problem::from(&svm);
+
+        assert_eq!(problem.is_inlier(), None);
+
+        problem.features()[0] = 0.5;
+        problem.features()[1] = 0.5;
+        svm.predict_value(&mut problem)?;
+
+        assert_eq!(problem.solution(), Solution::Inlier(true));
+        assert_eq!(problem.is_inlier(), Some(true));
+
+        problem.features()[0] = 5.0;
+        problem.features()[1] = 5.0;
+        svm.predict_value(&mut problem)?;
+
+        assert_eq!(problem.is_inlier(), Some(false));
+
+        Ok(())
+    }
+}
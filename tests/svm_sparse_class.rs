@@ -90,3 +90,75 @@ mod svm_sparse_class {
     // test_model!(m_nusvm_rbf, "m_nusvm_rbf.libsvm", false, [0, 7], []);
     // test_model!(m_nusvm_sigmoid, "m_nusvm_sigmoid.libsvm", false, [0, 7], []);
 }
+
+#[cfg(test)]
+mod svm_sparse_grouping {
+    use ffsvm::{Error, Predict, Problem, SparseSVM};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn grouped_support_vectors_predict_the_same() -> Result<(), Error> {
+        let model = include_str!("data_sparse/m_csvm_rbf_prob.libsvm");
+        let svm = SparseSVM::try_from(model)?;
+        let mut grouped = SparseSVM::try_from(model)?;
+
+        grouped.group_support_vectors();
+
+        for seed in 0 .. 16 {
+            let mut problem = Problem::from(&svm);
+            let mut problem_grouped = Problem::from(&grouped);
+
+            for i in (seed .. 128).step_by(seed + 3) {
+                let value = (i as f32 * 0.37).sin().abs() + 0.0001;
+                problem.features()[i] = value;
+                problem_grouped.features()[i] = value;
+            }
+
+            svm.predict_probability(&mut problem)?;
+            grouped.predict_probability(&mut problem_grouped)?;
+
+            assert_eq!(problem.solution(), problem_grouped.solution());
+
+            for (a, b) in problem.probabilities().iter().zip(problem_grouped.probabilities()) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod svm_sparse_gaps {
+    use ffsvm::{DenseSVM, Error, Predict, Problem, Solution, SparseSVM};
+    use std::convert::TryFrom;
+
+    const MODEL_WITH_GAPS: &str = "svm_type c_svc\nkernel_type linear\nnr_class 2\ntotal_sv 2\nrho 0\nlabel 1 2\nnr_sv 1 1\nSV\n1 1:0.5 5:0.2\n-1 2:1\n";
+
+    #[test]
+    fn sparse_models_may_skip_attributes() -> Result<(), Error> {
+        let svm = SparseSVM::try_from(MODEL_WITH_GAPS)?;
+        let mut problem = Problem::from(&svm);
+
+        // Only attribute 5 is set, so only the first support vector contributes (+0.2).
+        problem.features()[5] = 1.0;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(1));
+
+        // Only attribute 2 is set, so only the second support vector contributes (-1.0).
+        problem.clear();
+        problem.features()[2] = 1.0;
+        svm.predict_value(&mut problem)?;
+        assert_eq!(problem.solution(), Solution::Label(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dense_models_require_consecutive_attributes() {
+        match DenseSVM::try_from(MODEL_WITH_GAPS) {
+            Err(Error::AttributesUnordered { index: 5, last_index: 1, .. }) => {}
+            other => panic!("Unexpected result {:?}", other.err()),
+        }
+    }
+}